@@ -111,14 +111,31 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let container = docker.containers().get(&id);
             let tty_multiplexer = container.attach().await?;
 
-            let (mut reader, _writer) = tty_multiplexer.split();
+            let (mut reader, mut writer) = tty_multiplexer.split();
 
-            while let Some(tty_result) = reader.next().await {
-                match tty_result {
-                    Ok(chunk) => print_chunk(chunk),
-                    Err(e) => eprintln!("Error: {}", e),
+            let read_output = async {
+                while let Some(tty_result) = reader.next().await {
+                    match tty_result {
+                        Ok(chunk) => print_chunk(chunk),
+                        Err(e) => eprintln!("Error: {}", e),
+                    }
                 }
-            }
+            };
+
+            let write_input = async {
+                use futures::AsyncWriteExt;
+                use tokio::io::AsyncBufReadExt;
+
+                let mut lines = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    if let Err(e) = writer.write_all(format!("{line}\n").as_bytes()).await {
+                        eprintln!("Error: {}", e);
+                        break;
+                    }
+                }
+            };
+
+            futures::join!(read_output, write_input);
         }
         Cmd::CopyFrom {
             id,
@@ -200,9 +217,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 ContainerCreateOpts::builder()
                     .image(image)
                     .name(name)
-                    .build()
+                    .build()?
             } else {
-                ContainerCreateOpts::builder().image(image).build()
+                ContainerCreateOpts::builder().image(image).build()?
             };
             match docker.containers().create(&opts).await {
                 Ok(info) => println!("{:?}", info),
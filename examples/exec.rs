@@ -21,8 +21,8 @@ enum Cmd {
     /// Resize the TTY session used by an exec instance.
     Resize {
         exec: String,
-        width: u64,
-        height: u64,
+        width: u16,
+        height: u16,
     },
 }
 
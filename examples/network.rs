@@ -59,7 +59,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             use docker_api::opts::NetworkCreateOpts;
             match docker
                 .networks()
-                .create(&NetworkCreateOpts::builder(network).driver(driver).build())
+                .create(&NetworkCreateOpts::builder(network).driver(driver).build()?)
                 .await
             {
                 Ok(info) => println!("{:#?}", info),
@@ -5,6 +5,7 @@ use crate::{
     conn::{Headers, Payload},
     models, Result,
 };
+use containers_api::url::{construct_ep, encoded_pair};
 
 impl_api_ty!(Config => name);
 
@@ -14,7 +15,24 @@ impl Config {
         Delete -> &format!("/configs/{}", cfg.name), ()
     }
 
-    // TODO: add Config::update
+    api_doc! { Config => Update
+    |
+    /// Update this config. Only the labels of a config can be updated, other fields of `spec`
+    /// must match the current specification. The `version` must be the current version of the
+    /// config object as returned by [`Config::inspect`](Config::inspect).
+    pub async fn update(&self, version: u64, spec: &models::ConfigSpec) -> Result<()> {
+        self.docker
+            .post(
+                &construct_ep(
+                    format!("/configs/{}/update", self.name),
+                    Some(encoded_pair("version", version)),
+                ),
+                Payload::Json(serde_json::to_string(spec)?),
+                Headers::none(),
+            )
+            .await
+            .map(|_| ())
+    }}
 }
 
 impl Configs {
@@ -99,7 +117,7 @@ pub mod opts {
         pub fn new<N, D>(name: N, data: D) -> Self
         where
             N: Into<String>,
-            D: AsRef<str>,
+            D: AsRef<[u8]>,
         {
             Self {
                 name: name.into(),
@@ -135,3 +153,30 @@ pub mod opts {
 }
 
 pub use opts::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::MockServer;
+
+    #[test]
+    fn config_create_opts() {
+        let opts =
+            ConfigCreateOpts::new("nginx.conf", b"worker_processes 1;\n").add_label("env", "prod");
+
+        let body: serde_json::Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(body["Name"], "nginx.conf");
+        assert_eq!(body["Data"], "d29ya2VyX3Byb2Nlc3NlcyAxOwo=");
+        assert_eq!(body["Labels"], serde_json::json!({"env": "prod"}));
+    }
+
+    #[tokio::test]
+    async fn config_delete() {
+        let server = MockServer::start("204 No Content", "").await;
+        server.docker().configs().get("abc").delete().await.unwrap();
+        assert_eq!(
+            server.requests(),
+            vec![("DELETE".to_string(), "/v1.41/configs/abc".to_string())]
+        );
+    }
+}
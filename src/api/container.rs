@@ -1,41 +1,82 @@
 //! Create and manage containers.
 use crate::models;
 use crate::opts::{
-    ContainerCommitOpts, ContainerCreateOpts, ContainerListOpts, ContainerPruneOpts,
-    ContainerRemoveOpts,
+    ContainerAttachOpts, ContainerCommitOpts, ContainerCreateOpts, ContainerListOpts,
+    ContainerPruneOpts, ContainerRemoveOpts, ContainerResizeOpts, ContainerUpdateOpts, PullOpts,
+    Signal, WaitCondition,
 };
 
-use std::{io, path::Path, str, time::Duration};
+use std::{path::Path, pin::Pin, str, time::Duration};
 
 use futures_util::{
     io::{AsyncRead, AsyncWrite},
-    Stream, TryStreamExt,
+    Stream, StreamExt, TryFutureExt, TryStreamExt,
 };
-use hyper::Body;
+use hyper::{body::Bytes, Body};
 use serde::Deserialize;
 
 use crate::{
     api::Exec,
-    conn::{decode_chunk, Headers, Multiplexer as TtyMultiplexer, Payload, TtyChunk},
+    conn::{decode_chunk, tty, Headers, Multiplexer as TtyMultiplexer, Payload, TtyChunk},
     opts::ExecCreateOpts,
+    stream::{JsonLineDecoder, LogLines},
     Error, Result,
 };
-use containers_api::url::{append_query, construct_ep, encoded_pair};
+use containers_api::url::{append_query, construct_ep, encoded_pair, encoded_pairs};
+
+/// Interval between inspecting a container in [`Container::wait_for_healthy`].
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_millis(250);
 
 impl_api_ty!(Container => id);
 
 impl Container {
     impl_api_ep! {container: Container, resp
         Inspect -> &format!("/containers/{}/json", container.id), models::ContainerInspect200Response
-        Logs -> &format!("/containers/{}/logs", container.id), ()
-        DeleteWithOpts -> &format!("/containers/{}", container.id), String, delete
     }
 
+    api_doc! { Container => Inspect
+    |
+    /// Same as [`inspect`](Container::inspect) but returns
+    /// [`ContainerDetails`](models::ContainerDetails), keeping fields unknown to this version of
+    /// the library.
+    pub async fn details(&self) -> Result<models::ContainerDetails> {
+        self.docker
+            .get_json(&format!("/containers/{}/json", self.id))
+            .await
+    }}
+
+    api_doc! { Container => Delete
+    |
+    /// Delete this Container.
+    /// Use [`delete`](Container::delete) to delete without options.
+    ///
+    /// Removing a running container fails with a `409 Conflict`
+    /// [`Error::Fault`](crate::Error::Fault) unless
+    /// [`force`](crate::opts::ContainerRemoveOptsBuilder::force) is set.
+    pub async fn remove(&self, opts: &ContainerRemoveOpts) -> Result<String> {
+        let ep = construct_ep(format!("/containers/{}", self.id), opts.serialize());
+        self.docker
+            .delete(&ep)
+            .await
+            .map_err(|e| running_container_error(&self.id, e))
+    }}
+
+    api_doc! { Container => Delete
+    |
+    /// Delete this Container.
+    /// Use [`remove`](Container::remove) to customize options.
+    pub async fn delete(&self) -> Result<String> {
+        self.docker
+            .delete(&format!("/containers/{}", self.id))
+            .await
+            .map_err(|e| running_container_error(&self.id, e))
+    }}
+
     api_doc! { Container => Top
     |
     /// Returns a `top` view of information about the container process.
     /// On Unix systems, this is done by running the ps command. This endpoint is not supported on Windows.
-    pub async fn top(&self, psargs: Option<&str>) -> Result<models::ContainerTop200Response> {
+    pub async fn top(&self, psargs: Option<&str>) -> Result<models::ContainerTop> {
         let mut ep = format!("/containers/{}/top", self.id);
         if let Some(ref args) = psargs {
             append_query(&mut ep, encoded_pair("ps_args", args));
@@ -44,13 +85,13 @@ impl Container {
     }}
 
     /// Attaches a multiplexed TCP stream to the container that can be used to read Stdout, Stderr and write Stdin.
-    async fn attach_raw(&self) -> Result<impl AsyncRead + AsyncWrite + Send + '_> {
+    async fn attach_raw(
+        &self,
+        opts: &ContainerAttachOpts,
+    ) -> Result<impl AsyncRead + AsyncWrite + Send + '_> {
         self.docker
             .post_upgrade_stream(
-                format!(
-                    "/containers/{}/attach?stream=1&stdout=1&stderr=1&stdin=1",
-                    self.id
-                ),
+                construct_ep(format!("/containers/{}/attach", self.id), opts.serialize()),
                 Payload::empty(),
             )
             .await
@@ -63,22 +104,137 @@ impl Container {
     /// The [`TtyMultiplexer`](TtyMultiplexer) implements Stream for returning Stdout and Stderr chunks. It also implements [`AsyncWrite`](futures_util::io::AsyncWrite) for writing to Stdin.
     ///
     /// The multiplexer can be split into its read and write halves with the [`split`](TtyMultiplexer::split) method
+    ///
+    /// Both halves share the same underlying connection and can be driven concurrently. Bytes
+    /// written to the write half are sent as-is to the container's stdin, which requires the
+    /// container to be created with [`attach_stdin`](crate::opts::ContainerCreateOptsBuilder::attach_stdin)
+    /// set to `true`, otherwise the input is discarded by the daemon.
+    ///
+    /// The output of containers created with a TTY is a raw stream that is returned as
+    /// [`TtyChunk::StdOut`](TtyChunk::StdOut) chunks. The container is inspected to find out
+    /// if it has a TTY.
+    ///
+    /// Attaches to the output stream, stdout, stderr and stdin, use
+    /// [`attach_with`](Container::attach_with) to customize the attached streams or to skip
+    /// inspecting the container by passing the known [`tty`](crate::opts::ContainerAttachOptsBuilder::tty) mode.
     pub async fn attach(&self) -> Result<TtyMultiplexer<'_>> {
-        self.attach_raw().await.map(|s| TtyMultiplexer::new(s, decode_chunk))
+        let opts = ContainerAttachOpts::builder()
+            .stream(true)
+            .stdout(true)
+            .stderr(true)
+            .stdin(true)
+            .build();
+        self.attach_with(&opts).await
+    }}
+
+    api_doc! { Container => Attach
+    |
+    /// Attaches a [`TtyMultiplexer`](TtyMultiplexer) to the container with custom options, for
+    /// example to skip replaying the logs of the container or to attach only to stderr.
+    pub async fn attach_with(&self, opts: &ContainerAttachOpts) -> Result<TtyMultiplexer<'_>> {
+        opts.validate()?;
+        let tty = match opts.tty() {
+            Some(tty) => tty,
+            None => self.is_tty().await?,
+        };
+        self.attach_raw(opts).await.map(|s| {
+            if tty {
+                TtyMultiplexer::new(s, tty::decode_raw)
+            } else {
+                TtyMultiplexer::new(s, decode_chunk)
+            }
+        })
+    }}
+
+    api_doc! { Container => Logs
+    |
+    /// Returns a stream of logs from a Container.
+    ///
+    /// The logs of containers created with a TTY are a raw stream that is returned as
+    /// [`TtyChunk::StdOut`](TtyChunk::StdOut) chunks. Unless the
+    /// [`tty`](crate::opts::LogsOptsBuilder::tty) mode is passed the container is inspected
+    /// first to find out if it has a TTY.
+    pub fn logs<'docker>(
+        &'docker self,
+        opts: &crate::opts::LogsOpts,
+    ) -> impl Stream<Item = Result<TtyChunk>> + Unpin + 'docker {
+        let ep = construct_ep(format!("/containers/{}/logs", self.id), opts.serialize());
+        let tty = opts.tty();
+
+        Box::pin(
+            async move {
+                let tty = match tty {
+                    Some(tty) => tty,
+                    None => self.is_tty().await?,
+                };
+                Ok::<_, Error>(decode_output(Box::pin(self.docker.get_stream(ep)), tty))
+            }
+            .try_flatten_stream(),
+        )
+    }}
+
+    api_doc! { Container => Logs
+    |
+    /// Follows the logs of a Container like [`Container::logs`] and reconnects if the connection
+    /// to the daemon drops.
+    ///
+    /// `follow` and `timestamps` are always set, so every returned chunk is a single log line
+    /// starting with its timestamp. When the stream fails with a transport error the logs are
+    /// requested again `since` the timestamp of the last returned line, skipping lines that
+    /// were already returned, so that no line is lost or returned twice. The stream ends with
+    /// the error if reconnecting fails 5 times in a row.
+    pub fn logs_follow_resilient<'docker>(
+        &'docker self,
+        opts: &crate::opts::LogsOpts,
+    ) -> impl Stream<Item = Result<TtyChunk>> + Unpin + 'docker {
+        let query = opts
+            .serialize()
+            .map(|query| {
+                url::form_urlencoded::parse(query.as_bytes())
+                    .into_owned()
+                    .filter(|(key, _)| key != "follow" && key != "timestamps")
+                    .collect()
+            })
+            .unwrap_or_default();
+        let logs = ResilientLogs {
+            container: self,
+            query,
+            tty: opts.tty(),
+            stream: None,
+            lines: LogLines::default(),
+            reconnects: 0,
+            done: false,
+        };
+
+        Box::pin(futures_util::stream::unfold(logs, |mut logs| async move {
+            logs.next().await.map(|item| (item, logs))
+        }))
     }}
 
+    /// Returns `true` if the container was created with a TTY.
+    async fn is_tty(&self) -> Result<bool> {
+        self.inspect().await.map(|info| {
+            info.config
+                .and_then(|config| config.tty)
+                .unwrap_or_default()
+        })
+    }
+
     api_doc! { Container => Changes
     |
     /// Returns a set of changes made to the container instance.
-    pub async fn changes(&self) -> Result<Option<models::ContainerChanges200Response>> {
+    pub async fn changes(&self) -> Result<Vec<models::FilesystemChange>> {
+        // the daemon returns `null` instead of an empty list if there are no changes
         self.docker
             .get_json(&format!("/containers/{}/changes", self.id))
             .await
+            .map(Option::unwrap_or_default)
     }}
 
     api_doc! { Container => Export
     |
-    /// Exports the current docker container into a tarball.
+    /// Exports the current docker container into a tarball. The tarball is streamed in chunks as
+    /// it is received, transport errors are returned as `Err` items of the stream.
     pub fn export(&self) -> impl Stream<Item = Result<Vec<u8>>> + '_ {
         self.docker
             .get_stream(format!("/containers/{}/export", self.id))
@@ -88,93 +244,124 @@ impl Container {
     api_doc! { Container => Stats
     |
     /// Returns a stream of stats specific to this container instance.
-    pub fn stats(&self) -> impl Stream<Item = Result<serde_json::Value>> + Unpin + '_ {
-        let codec = asynchronous_codec::LinesCodec {};
-
-        let reader = Box::pin(
+    pub fn stats(&self) -> impl Stream<Item = Result<models::ContainerStats>> + Unpin + '_ {
+        JsonLineDecoder::new(Box::pin(
             self.docker
-                .get_stream(format!("/containers/{}/stats", self.id))
-                .map_err(|e| io::Error::new(io::ErrorKind::Other, e)),
-        )
-        .into_async_read();
-
-        Box::pin(
-            asynchronous_codec::FramedRead::new(reader, codec)
-                .map_err(Error::IO)
-                .and_then(|s: String| async move {
-                    log::trace!("{}", s);
-                    serde_json::from_str(&s).map_err(Error::SerdeJsonError)
-                }),
-        )
+                .get_stream(format!("/containers/{}/stats", self.id)),
+        ))
     }}
 
-    api_doc! { Container => Start
+    api_doc! { Container => Stats
     |
-    /// Start the container instance.
-    pub async fn start(&self) -> Result<()> {
+    /// Returns a single snapshot of stats specific to this container instance. The daemon
+    /// doesn't wait for a second reading to compute the CPU usage delta, so
+    /// [`ContainerStats::cpu_percentage`](crate::models::ContainerStats::cpu_percentage) of the
+    /// returned snapshot is `0.0`. This is much cheaper than [`Container::stats`] for a single
+    /// read.
+    pub async fn stats_once(&self) -> Result<models::ContainerStats> {
+        self.docker
+            .get_json(&format!(
+                "/containers/{}/stats?stream=false&one-shot=true",
+                self.id
+            ))
+            .await
+    }}
+
+    /// Sends a `POST` request to one of the container lifecycle endpoints like `start` or `stop`.
+    async fn lifecycle_action(&self, action: &str, query: Option<String>) -> Result<()> {
         self.docker
             .post_string(
-                &format!("/containers/{}/start", self.id),
+                &lifecycle_ep(&self.id, action, query),
                 Payload::empty(),
                 Headers::none(),
             )
             .await
             .map(|_| ())
+    }
+
+    api_doc! { Container => Start
+    |
+    /// Start the container instance.
+    ///
+    /// Returns [`Error::NotModified`](crate::Error::NotModified) if the container is already running.
+    pub async fn start(&self) -> Result<()> {
+        self.lifecycle_action("start", None).await
     }}
 
     api_doc! { Container => Stop
     |
-    /// Stop the container instance.
-    pub async fn stop(&self, wait: Option<Duration>) -> Result<()> {
-        let mut ep = format!("/containers/{}/stop", self.id);
-        if let Some(w) = wait {
-            append_query(&mut ep, encoded_pair("t", w.as_secs()));
-        }
-        self.docker
-            .post_string(&ep, Payload::empty(), Headers::none())
+    /// Stop the container instance. If `wait` is provided the container is killed after that
+    /// many seconds, otherwise the daemon default is used.
+    ///
+    /// Returns [`StopOutcome::AlreadyStopped`](models::StopOutcome::AlreadyStopped) if the
+    /// container wasn't running.
+    pub async fn stop(&self, wait: Option<Duration>) -> Result<models::StopOutcome> {
+        match self
+            .lifecycle_action("stop", wait.map(|w| encoded_pair("t", w.as_secs())))
             .await
-            .map(|_| ())
+        {
+            Ok(()) => Ok(models::StopOutcome::Stopped),
+            Err(Error::NotModified) => Ok(models::StopOutcome::AlreadyStopped),
+            Err(e) => Err(e),
+        }
     }}
 
+    /// Stops the container and removes it, which is the usual way to tear down a container
+    /// once it's no longer needed. The container is killed if it doesn't stop within `wait`,
+    /// otherwise the daemon default grace period is used. If `remove_volumes` is set the
+    /// anonymous volumes of the container are removed as well.
+    ///
+    /// Containers that are already stopped are removed right away. The removal is forced so
+    /// that a container restarted in the meantime doesn't fail the teardown.
+    pub async fn stop_and_remove(
+        &self,
+        wait: Option<Duration>,
+        remove_volumes: bool,
+    ) -> Result<()> {
+        self.stop(wait).await?;
+        let opts = ContainerRemoveOpts::builder()
+            .force(true)
+            .volumes(remove_volumes)
+            .build();
+        self.remove(&opts).await.map(|_| ())
+    }
+
     api_doc! { Container => Restart
     |
-    /// Restart the container instance.
+    /// Restart the container instance. If `wait` is provided the container is killed after that
+    /// many seconds, otherwise the daemon default is used.
     pub async fn restart(&self, wait: Option<Duration>) -> Result<()> {
-        let mut ep = format!("/containers/{}/restart", self.id);
-        if let Some(w) = wait {
-            append_query(&mut ep, encoded_pair("t", w.as_secs()));
-        }
-        self.docker
-            .post_string(&ep, Payload::empty(), Headers::none())
+        self.lifecycle_action("restart", wait.map(|w| encoded_pair("t", w.as_secs())))
             .await
-            .map(|_| ())
     }}
 
     api_doc! { Container => Kill
     |
-    /// Kill the container instance.
+    /// Kill the container instance. If no `signal` is provided `SIGKILL` is sent.
+    ///
+    /// The `signal` is passed to the daemon as is, for example `SIGTERM`, `SIGRTMIN+3` or a
+    /// signal number. Use [`Container::kill_with`] to send a [`Signal`] checked beforehand.
     pub async fn kill(&self, signal: Option<&str>) -> Result<()> {
-        let mut ep = format!("/containers/{}/kill", self.id);
-        if let Some(sig) = signal {
-            append_query(&mut ep, encoded_pair("signal", sig));
-        }
-        self.docker
-            .post_string(&ep, Payload::empty(), Headers::none())
+        self.lifecycle_action("kill", signal.map(|sig| encoded_pair("signal", sig)))
             .await
-            .map(|_| ())
     }}
 
-    api_doc! { Container => Rename
+    api_doc! { Container => Kill
     |
-    /// Rename the container instance.
-    pub async fn rename(&self, name: &str) -> Result<()> {
+    /// Send the `signal` to the container instance.
+    pub async fn kill_with(&self, signal: Signal) -> Result<()> {
+        self.lifecycle_action("kill", Some(encoded_pair("signal", signal)))
+            .await
+    }}
+
+    api_doc! { Container => Resize
+    |
+    /// Resize the TTY of this container. This only works if the container was created with `tty`
+    /// enabled.
+    pub async fn resize_tty(&self, opts: &ContainerResizeOpts) -> Result<()> {
         self.docker
             .post_string(
-                &format!(
-                    "/containers/{}/rename?{}",
-                    self.id,
-                    encoded_pair("name", name)
-                ),
+                &construct_ep(format!("/containers/{}/resize", self.id), opts.serialize()),
                 Payload::empty(),
                 Headers::none(),
             )
@@ -182,47 +369,106 @@ impl Container {
             .map(|_| ())
     }}
 
-    api_doc! { Container => Pause
+    api_doc! { Container => Update
     |
-    /// Pause the container instance.
-    pub async fn pause(&self) -> Result<()> {
+    /// Change resource limits and the restart policy of this container without recreating it.
+    pub async fn update(
+        &self,
+        opts: &ContainerUpdateOpts,
+    ) -> Result<models::ContainerUpdate200Response> {
         self.docker
-            .post_string(
-                &format!("/containers/{}/pause", self.id),
-                Payload::empty(),
+            .post_json(
+                format!("/containers/{}/update", self.id),
+                Payload::Json(opts.serialize()?),
                 Headers::none(),
             )
             .await
-            .map(|_| ())
+    }}
+
+    api_doc! { Container => Rename
+    |
+    /// Rename the container instance.
+    ///
+    /// The `name` must match `[a-zA-Z0-9][a-zA-Z0-9_.-]+`, invalid names are rejected without
+    /// sending a request.
+    pub async fn rename(&self, name: &str) -> Result<()> {
+        validate_container_name(name)?;
+
+        self.lifecycle_action("rename", Some(encoded_pair("name", name)))
+            .await
+            .map_err(|e| name_conflict_error(name, e))
+    }}
+
+    api_doc! { Container => Pause
+    |
+    /// Pause the container instance.
+    pub async fn pause(&self) -> Result<()> {
+        self.lifecycle_action("pause", None).await
     }}
 
     api_doc! { Container => Unpause
     |
     /// Unpause the container instance.
     pub async fn unpause(&self) -> Result<()> {
-        self.docker
-            .post_string(
-                &format!("/containers/{}/unpause", self.id),
-                Payload::empty(),
-                Headers::none(),
-            )
-            .await
-            .map(|_| ())
+        self.lifecycle_action("unpause", None).await
     }}
 
     api_doc! { Container => Wait
     |
-    /// Wait until the container stops.
-    pub async fn wait(&self) -> Result<models::ContainerWaitResponse> {
+    /// Wait until the container meets the given `condition`.
+    ///
+    /// A container exiting with a nonzero code is not an error, check the returned
+    /// [`status_code`](models::ContainerWaitInfo::status_code) instead.
+    pub async fn wait(&self, condition: WaitCondition) -> Result<models::ContainerWaitInfo> {
         self.docker
             .post_json(
-                format!("/containers/{}/wait", self.id),
+                construct_ep(
+                    format!("/containers/{}/wait", self.id),
+                    Some(encoded_pair("condition", condition.as_ref())),
+                ),
                 Payload::empty(),
                 Headers::none(),
             )
             .await
     }}
 
+    /// Wait until the health check of the container reports it as healthy by periodically
+    /// inspecting it.
+    ///
+    /// Fails with [`Error::Unhealthy`] if the container becomes unhealthy and with
+    /// [`Error::Timeout`] if it isn't healthy within `timeout`. The container must have a health
+    /// check configured.
+    pub async fn wait_for_healthy(&self, timeout: Duration) -> Result<()> {
+        let poll = async {
+            loop {
+                let health = self.inspect().await?.state.and_then(|state| state.health);
+                match health.as_ref().and_then(|health| health.status.as_deref()) {
+                    Some("healthy") => return Ok(()),
+                    Some("unhealthy") => {
+                        let output = health
+                            .as_ref()
+                            .and_then(|health| health.log.as_ref())
+                            .and_then(|log| log.last())
+                            .and_then(|result| result.output.as_deref())
+                            .unwrap_or_default();
+                        return Err(Error::Unhealthy(output.trim().to_owned()));
+                    }
+                    None | Some("none") => {
+                        return Err(Error::StringError(format!(
+                            "container `{}` has no health check",
+                            self.id
+                        )))
+                    }
+                    _ => tokio::time::sleep(HEALTH_POLL_INTERVAL).await,
+                }
+            }
+        };
+
+        tokio::time::timeout(timeout, poll)
+            .await
+            .map_err(|_| Error::Timeout)?
+    }
+
     api_doc! { Exec
     |
     /// Execute a command in this container.
@@ -233,6 +479,15 @@ impl Container {
         Exec::create_and_start(&self.docker, &self.id, opts)
     }}
 
+    api_doc! { Exec => Create
+    |
+    /// Create an exec instance in this container without starting it. Use
+    /// [`Exec::start`](Exec::start) to run it and [`Exec::inspect`](Exec::inspect) to get its
+    /// exit code afterwards.
+    pub async fn create_exec(&self, opts: &ExecCreateOpts) -> Result<Exec> {
+        Exec::create(self.docker.clone(), &self.id, opts).await
+    }}
+
     api_doc! { Container => Archive
     |
     /// Copy a file/folder from the container.  The resulting stream is a tarball of the extracted
@@ -300,7 +555,9 @@ impl Container {
 
     api_doc! { Container => ArchiveInfo
     |
-    /// Get information about files in a container.
+    /// Get information about files in a container. Returns the raw JSON of the
+    /// `X-Docker-Container-Path-Stat` header, use [`stat_path`](Container::stat_path) to get it
+    /// deserialized.
     pub async fn stat_file<P>(&self, path: P) -> Result<String>
     where
         P: AsRef<Path>,
@@ -341,6 +598,15 @@ impl Container {
         }
     }}
 
+    api_doc! { Container => ArchiveInfo
+    |
+    /// Get information about a file or directory in a container.
+    pub async fn stat_path(&self, path: impl AsRef<Path>) -> Result<models::PathStat> {
+        self.stat_file(path)
+            .await
+            .and_then(|stat| serde_json::from_str(&stat).map_err(Error::from))
+    }}
+
     api_doc! { Image => Commit
     |
     /// Create a new image from this container
@@ -375,17 +641,934 @@ impl Containers {
     api_doc! { Containers => Create
     |
     /// Create a container
+    ///
+    /// If a name is set in `opts` and a container with that name already exists the returned
+    /// [`Error::Fault`](crate::Error::Fault) has a `409 Conflict` code.
+    ///
+    /// Returns [`Error::InvalidPlatform`](crate::Error::InvalidPlatform) without sending the
+    /// request if the platform set in `opts` is not in the format `os/arch[/variant]`.
     pub async fn create(&self, opts: &ContainerCreateOpts) -> Result<Container> {
-        let ep = if let Some(name) = opts.name() {
-            construct_ep("/containers/create", Some(encoded_pair("name", name)))
-        } else {
-            "/containers/create".to_owned()
-        };
+        if let Some(platform) = opts.platform() {
+            crate::opts::validate_platform(platform)?;
+        }
+
         self.docker
-            .post_json(&ep, Payload::Json(opts.serialize()?), Headers::none())
+            .post_json(&create_ep(opts), Payload::Json(opts.serialize()?), Headers::none())
             .await
             .map(|resp: models::ContainerCreate201Response| {
                 Container::new(self.docker.clone(), resp.id)
             })
+            .map_err(|e| match opts.name() {
+                Some(name) => name_conflict_error(name, e),
+                None => e,
+            })
     }}
+
+    /// Returns the container with the name set in `opts` if it exists, otherwise creates it.
+    ///
+    /// The existing container is returned as is, even if it was created with different options.
+    /// Fails if no name is set in `opts`.
+    pub async fn get_or_create(&self, opts: &ContainerCreateOpts) -> Result<Container> {
+        let name = opts.name().ok_or_else(|| {
+            Error::StringError("a container name is required to look up the container".into())
+        })?;
+
+        match self.get(name).inspect().await {
+            Ok(info) => return Ok(self.get(info.id.as_deref().unwrap_or(name))),
+            Err(Error::Fault { code, .. }) if code == hyper::StatusCode::NOT_FOUND => {}
+            Err(e) => return Err(e),
+        }
+
+        match self.create(opts).await {
+            // created concurrently by someone else
+            Err(Error::Fault { code, .. }) if code == hyper::StatusCode::CONFLICT => {
+                Ok(self.get(name))
+            }
+            result => result,
+        }
+    }
+
+    /// Creates a container like [`Containers::create`](Containers::create), pulling the image
+    /// first if it is not present on the docker host, like `docker run` does.
+    ///
+    /// The image is only pulled if creating the container fails because the image is missing,
+    /// creating the container is then retried once. Failures of the pull are returned as
+    /// [`Error::ImagePull`](crate::Error::ImagePull). Images requiring registry authentication
+    /// have to be pulled with [`Images::pull`](crate::Images::pull) instead.
+    pub async fn create_with_pull(&self, opts: &ContainerCreateOpts) -> Result<Container> {
+        match self.create(opts).await {
+            Err(Error::Fault { code, message })
+                if code == hyper::StatusCode::NOT_FOUND && message.contains("No such image") =>
+            {
+                let image = opts.image().ok_or(Error::Fault { code, message })?;
+                self.pull_image(image, opts.platform()).await?;
+                self.create(opts).await
+            }
+            result => result,
+        }
+    }
+
+    /// Pulls `image` draining the progress stream.
+    async fn pull_image(&self, image: &str, platform: Option<&str>) -> Result<()> {
+        let (name, tag) = split_image_reference(image);
+        let mut opts = PullOpts::builder().image(name);
+        if let Some(tag) = tag {
+            opts = opts.tag(tag);
+        }
+        if let Some(platform) = platform {
+            opts = opts.platform(platform);
+        }
+
+        let pull_error = |message: String| Error::ImagePull {
+            image: image.to_owned(),
+            message,
+        };
+        let images = self.docker.images();
+        let mut stream = images.pull(&opts.build());
+        while let Some(chunk) = stream.next().await {
+            match chunk {
+                Ok(models::ImageBuildChunk::Error { error, .. }) => return Err(pull_error(error)),
+                Ok(_) => {}
+                Err(e) => return Err(pull_error(e.to_string())),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits an image reference like `nginx:1.23` or `nginx@sha256:...` into the image name and the
+/// tag or digest if there is one.
+fn split_image_reference(image: &str) -> (&str, Option<&str>) {
+    if let Some((name, digest)) = image.split_once('@') {
+        return (name, Some(digest));
+    }
+    match image.rsplit_once(':') {
+        // a colon followed by a slash belongs to the registry port like `localhost:5000/nginx`
+        Some((name, tag)) if !tag.contains('/') => (name, Some(tag)),
+        _ => (image, None),
+    }
+}
+
+/// Checks that `name` is a valid container name as defined by `[a-zA-Z0-9][a-zA-Z0-9_.-]+`.
+fn validate_container_name(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let valid = matches!(chars.next(), Some(c) if c.is_ascii_alphanumeric())
+        && !chars.as_str().is_empty()
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidContainerName(name.to_owned()))
+    }
+}
+
+fn create_ep(opts: &ContainerCreateOpts) -> String {
+    let query: Vec<_> = [("name", opts.name()), ("platform", opts.platform())]
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|value| (key, value)))
+        .collect();
+
+    construct_ep(
+        "/containers/create",
+        (!query.is_empty()).then(|| encoded_pairs(query)),
+    )
+}
+
+/// Adds the container name to the message of a `409 Conflict` error.
+fn name_conflict_error(name: &str, e: Error) -> Error {
+    match e {
+        Error::Fault { code, message } if code == hyper::StatusCode::CONFLICT => Error::Fault {
+            code,
+            message: format!("container name `{}` is already in use - {}", name, message),
+        },
+        e => e,
+    }
+}
+
+/// Points to the `force` option if removing a container failed because it is running.
+fn running_container_error(id: &crate::Id, e: Error) -> Error {
+    match e {
+        Error::Fault { code, message }
+            if code == hyper::StatusCode::CONFLICT && message.contains("running") =>
+        {
+            Error::Fault {
+                code,
+                message: format!(
+                    "container `{}` is running, stop it first or remove it with `force` set - {}",
+                    id, message
+                ),
+            }
+        }
+        e => e,
+    }
+}
+
+/// Number of consecutive reconnects of [`Container::logs_follow_resilient`] before giving up.
+const MAX_LOG_RECONNECTS: u32 = 5;
+/// Delay before the first reconnect, doubled on every further attempt.
+const LOG_RECONNECT_DELAY: Duration = Duration::from_millis(100);
+
+/// State of [`Container::logs_follow_resilient`].
+struct ResilientLogs<'docker> {
+    container: &'docker Container,
+    /// Query of the logs request without `follow` and `timestamps`.
+    query: Vec<(String, String)>,
+    tty: Option<bool>,
+    stream: Option<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + 'docker>>>,
+    lines: LogLines,
+    reconnects: u32,
+    done: bool,
+}
+
+impl ResilientLogs<'_> {
+    async fn next(&mut self) -> Option<Result<TtyChunk>> {
+        loop {
+            if let Some(line) = self.lines.next_line() {
+                return Some(Ok(line));
+            }
+            if self.done {
+                return None;
+            }
+
+            let tty = match self.tty {
+                Some(tty) => tty,
+                None => match self.container.is_tty().await {
+                    Ok(tty) => *self.tty.insert(tty),
+                    Err(e) => {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                },
+            };
+            let stream = match &mut self.stream {
+                Some(stream) => stream,
+                None => {
+                    let ep = self.endpoint();
+                    self.stream
+                        .insert(Box::pin(self.container.docker.get_stream(ep)))
+                }
+            };
+
+            // the output is decoded here as the multiplexed output decoder ends the stream
+            // without an error if the connection drops in the middle of a frame
+            match stream.next().await {
+                Some(Ok(chunk)) => {
+                    self.reconnects = 0;
+                    if tty {
+                        self.lines.push(TtyChunk::StdOut(chunk.to_vec()));
+                    } else {
+                        self.lines.push_multiplexed(&chunk);
+                    }
+                }
+                Some(Err(e))
+                    if self.reconnects < MAX_LOG_RECONNECTS && crate::docker::is_transient(&e) =>
+                {
+                    let delay =
+                        LOG_RECONNECT_DELAY.saturating_mul(2u32.saturating_pow(self.reconnects));
+                    log::debug!("following logs failed, reconnecting in {delay:?} - {e}");
+                    self.reconnects += 1;
+                    self.stream = None;
+                    self.lines.restart();
+                    tokio::time::sleep(delay).await;
+                }
+                Some(Err(e)) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+                None => {
+                    self.done = true;
+                    self.lines.finish();
+                }
+            }
+        }
+    }
+
+    /// Returns the logs endpoint continuing after the last returned line.
+    fn endpoint(&self) -> String {
+        let since = self.lines.since();
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        for (key, val) in &self.query {
+            // `tail` would skip lines written since the last returned one
+            if since.is_none() || (key != "since" && key != "tail") {
+                query.append_pair(key, val);
+            }
+        }
+        query
+            .append_pair("follow", "true")
+            .append_pair("timestamps", "true");
+        if let Some(since) = &since {
+            query.append_pair("since", since);
+        }
+
+        format!("/containers/{}/logs?{}", self.container.id, query.finish())
+    }
+}
+
+/// Decodes the output of a container. The output of containers with a TTY is a raw stream,
+/// otherwise stdout and stderr are multiplexed using 8 byte frame headers.
+fn decode_output<'a, S>(
+    stream: S,
+    is_tty: bool,
+) -> impl Stream<Item = Result<TtyChunk>> + Unpin + 'a
+where
+    S: Stream<Item = Result<Bytes>> + Unpin + 'a,
+{
+    if is_tty {
+        stream
+            .map_ok(|chunk| TtyChunk::StdOut(chunk.to_vec()))
+            .left_stream()
+    } else {
+        let stream = stream.map_err(|e| crate::conn::Error::Any(Box::new(e)));
+        Box::pin(tty::decode(stream).map_err(Error::Error)).right_stream()
+    }
+}
+
+fn lifecycle_ep(id: &crate::Id, action: &str, query: Option<String>) -> String {
+    construct_ep(format!("/containers/{id}/{action}"), query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::MockServer;
+
+    #[test]
+    fn container_lifecycle_endpoints() {
+        let id = crate::Id::from("test");
+
+        assert_eq!(lifecycle_ep(&id, "start", None), "/containers/test/start");
+        assert_eq!(lifecycle_ep(&id, "pause", None), "/containers/test/pause");
+        assert_eq!(
+            lifecycle_ep(&id, "unpause", None),
+            "/containers/test/unpause"
+        );
+        assert_eq!(
+            lifecycle_ep(
+                &id,
+                "stop",
+                Some(encoded_pair("t", Duration::from_secs(10).as_secs()))
+            ),
+            "/containers/test/stop?t=10"
+        );
+        assert_eq!(
+            lifecycle_ep(
+                &id,
+                "restart",
+                Some(encoded_pair("t", Duration::from_secs(10).as_secs()))
+            ),
+            "/containers/test/restart?t=10"
+        );
+        assert_eq!(
+            lifecycle_ep(&id, "kill", Some(encoded_pair("signal", "SIGHUP"))),
+            "/containers/test/kill?signal=SIGHUP"
+        );
+    }
+
+    fn chunk_output(chunk: TtyChunk) -> (&'static str, Vec<u8>) {
+        match chunk {
+            TtyChunk::StdIn(bytes) => ("stdin", bytes),
+            TtyChunk::StdOut(bytes) => ("stdout", bytes),
+            TtyChunk::StdErr(bytes) => ("stderr", bytes),
+        }
+    }
+
+    #[tokio::test]
+    async fn decodes_multiplexed_output() {
+        let mut payload = vec![1, 0, 0, 0, 0, 0, 0, 5];
+        payload.extend_from_slice(b"hello");
+        payload.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 4]);
+        payload.extend_from_slice(b"oops");
+        // frames may be split across chunks
+        let (first, second) = payload.split_at(10);
+        let stream = futures_util::stream::iter(vec![
+            Ok(Bytes::copy_from_slice(first)),
+            Ok(Bytes::copy_from_slice(second)),
+        ]);
+
+        let chunks: Vec<_> = decode_output(stream, false)
+            .map_ok(chunk_output)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(
+            chunks,
+            vec![("stdout", b"hello".to_vec()), ("stderr", b"oops".to_vec())]
+        );
+    }
+
+    #[tokio::test]
+    async fn decodes_raw_tty_output() {
+        // a TTY stream has no frame headers, the bytes must be returned unchanged
+        let stream = futures_util::stream::iter(vec![
+            Ok(Bytes::from_static(&[1, 0, 0, 0, 0, 0, 0, 5])),
+            Ok(Bytes::from_static(b"$ ls\r\n")),
+        ]);
+        let chunks: Vec<_> = decode_output(stream, true)
+            .map_ok(chunk_output)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(
+            chunks,
+            vec![
+                ("stdout", vec![1, 0, 0, 0, 0, 0, 0, 5]),
+                ("stdout", b"$ ls\r\n".to_vec()),
+            ]
+        );
+
+        let reader = futures_util::io::Cursor::new(b"raw tty output".to_vec());
+        let chunks: Vec<_> = futures_util::stream::unfold(reader, tty::decode_raw)
+            .map_ok(chunk_output)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(chunks, vec![("stdout", b"raw tty output".to_vec())]);
+    }
+
+    #[tokio::test]
+    async fn container_stop_outcome() {
+        let server = MockServer::start("204 No Content", "").await;
+        let outcome = server
+            .docker()
+            .containers()
+            .get("test")
+            .stop(None)
+            .await
+            .unwrap();
+        assert_eq!(outcome, models::StopOutcome::Stopped);
+        assert_eq!(
+            server.requests(),
+            vec![(
+                "POST".to_string(),
+                "/v1.41/containers/test/stop".to_string()
+            )]
+        );
+
+        let server = MockServer::start("304 Not Modified", "").await;
+        let outcome = server
+            .docker()
+            .containers()
+            .get("test")
+            .stop(None)
+            .await
+            .unwrap();
+        assert_eq!(outcome, models::StopOutcome::AlreadyStopped);
+
+        let server = MockServer::start("404 Not Found", "").await;
+        match server.docker().containers().get("test").stop(None).await {
+            Err(Error::Fault { code, .. }) => assert_eq!(code, 404),
+            res => panic!("Expected Error::Fault, got {:?}", res),
+        }
+    }
+
+    #[tokio::test]
+    async fn container_kill_signal() {
+        let server = MockServer::start("204 No Content", "").await;
+        let container = server.docker().containers().get("test");
+
+        container.kill(None).await.unwrap();
+        container.kill(Some("SIGPIPE")).await.unwrap();
+        container.kill(Some("SIGRTMIN+3")).await.unwrap();
+        container.kill_with(Signal::Term).await.unwrap();
+        container.kill_with("term".parse().unwrap()).await.unwrap();
+
+        let path = |path: &str| ("POST".to_string(), path.to_string());
+        assert_eq!(
+            server.requests(),
+            vec![
+                path("/v1.41/containers/test/kill"),
+                path("/v1.41/containers/test/kill?signal=SIGPIPE"),
+                path("/v1.41/containers/test/kill?signal=SIGRTMIN%2B3"),
+                path("/v1.41/containers/test/kill?signal=SIGTERM"),
+                path("/v1.41/containers/test/kill?signal=SIGTERM"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn container_wait_for_healthy() {
+        let server = MockServer::start_sequence(vec![
+            (
+                "200 OK",
+                r#"{"Id":"test","State":{"Health":{"Status":"starting"}}}"#,
+            ),
+            (
+                "200 OK",
+                r#"{"Id":"test","State":{"Health":{"Status":"healthy"}}}"#,
+            ),
+        ])
+        .await;
+        let container = server.docker().containers().get("test");
+
+        container
+            .wait_for_healthy(Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(server.requests().len(), 2);
+
+        let server = MockServer::start(
+            "200 OK",
+            r#"{"Id":"test","State":{"Health":{"Status":"unhealthy","Log":[{"ExitCode":1,"Output":"connection refused\n"}]}}}"#,
+        )
+        .await;
+        let container = server.docker().containers().get("test");
+        match container.wait_for_healthy(Duration::from_secs(5)).await {
+            Err(Error::Unhealthy(output)) => assert_eq!(output, "connection refused"),
+            res => panic!("Expected Error::Unhealthy, got {:?}", res),
+        }
+
+        let server = MockServer::start(
+            "200 OK",
+            r#"{"Id":"test","State":{"Health":{"Status":"starting"}}}"#,
+        )
+        .await;
+        let container = server.docker().containers().get("test");
+        let result = container.wait_for_healthy(Duration::from_millis(50)).await;
+        assert!(matches!(result, Err(Error::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn container_get_or_create() {
+        let opts = ContainerCreateOpts::builder()
+            .name("web")
+            .image("nginx")
+            .build()
+            .unwrap();
+
+        let server = MockServer::start("200 OK", r#"{"Id":"abc123"}"#).await;
+        let container = server
+            .docker()
+            .containers()
+            .get_or_create(&opts)
+            .await
+            .unwrap();
+        assert_eq!(container.id().as_ref(), "abc123");
+        assert_eq!(
+            server.requests(),
+            vec![("GET".to_string(), "/v1.41/containers/web/json".to_string())]
+        );
+
+        let server = MockServer::start_sequence(vec![
+            ("404 Not Found", r#"{"message":"No such container: web"}"#),
+            ("201 Created", r#"{"Id":"def456","Warnings":[]}"#),
+        ])
+        .await;
+        let container = server
+            .docker()
+            .containers()
+            .get_or_create(&opts)
+            .await
+            .unwrap();
+        assert_eq!(container.id().as_ref(), "def456");
+        assert_eq!(
+            server.requests(),
+            vec![
+                ("GET".to_string(), "/v1.41/containers/web/json".to_string()),
+                (
+                    "POST".to_string(),
+                    "/v1.41/containers/create?name=web".to_string()
+                ),
+            ]
+        );
+
+        let server = MockServer::start("500 Internal Server Error", "").await;
+        match server.docker().containers().get_or_create(&opts).await {
+            Err(Error::Fault { code, .. }) => assert_eq!(code, 500),
+            res => panic!("Expected Error::Fault, got {:?}", res.map(|_| ())),
+        }
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn container_create_with_pull() {
+        let opts = ContainerCreateOpts::builder()
+            .image("nginx:1.23")
+            .build()
+            .unwrap();
+
+        let server = MockServer::start("201 Created", r#"{"Id":"abc123","Warnings":[]}"#).await;
+        let container = server
+            .docker()
+            .containers()
+            .create_with_pull(&opts)
+            .await
+            .unwrap();
+        assert_eq!(container.id().as_ref(), "abc123");
+        assert_eq!(
+            server.requests(),
+            vec![("POST".to_string(), "/v1.41/containers/create".to_string())]
+        );
+
+        let server = MockServer::start_sequence(vec![
+            (
+                "404 Not Found",
+                r#"{"message":"No such image: nginx:1.23"}"#,
+            ),
+            (
+                "200 OK",
+                r#"{"status":"Pulling from library/nginx","id":"1.23"}"#,
+            ),
+            ("201 Created", r#"{"Id":"def456","Warnings":[]}"#),
+        ])
+        .await;
+        let container = server
+            .docker()
+            .containers()
+            .create_with_pull(&opts)
+            .await
+            .unwrap();
+        assert_eq!(container.id().as_ref(), "def456");
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        let (method, pull) = &requests[1];
+        assert_eq!(method, "POST");
+        let (path, query) = pull.split_once('?').unwrap();
+        assert_eq!(path, "/v1.41/images/create");
+        let query: std::collections::HashMap<_, _> =
+            url::form_urlencoded::parse(query.as_bytes()).collect();
+        assert_eq!(query["fromImage"], "nginx");
+        assert_eq!(query["tag"], "1.23");
+        assert_eq!(requests[2], requests[0]);
+
+        let server = MockServer::start_sequence(vec![
+            (
+                "404 Not Found",
+                r#"{"message":"No such image: nginx:1.23"}"#,
+            ),
+            (
+                "200 OK",
+                r#"{"error":"manifest unknown","errorDetail":{"message":"manifest unknown"}}"#,
+            ),
+        ])
+        .await;
+        match server.docker().containers().create_with_pull(&opts).await {
+            Err(Error::ImagePull { image, message }) => {
+                assert_eq!(image, "nginx:1.23");
+                assert_eq!(message, "manifest unknown");
+            }
+            res => panic!("Expected Error::ImagePull, got {:?}", res.map(|_| ())),
+        }
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn image_reference_split() {
+        assert_eq!(split_image_reference("nginx"), ("nginx", None));
+        assert_eq!(split_image_reference("nginx:1.23"), ("nginx", Some("1.23")));
+        assert_eq!(
+            split_image_reference("localhost:5000/nginx"),
+            ("localhost:5000/nginx", None)
+        );
+        assert_eq!(
+            split_image_reference("localhost:5000/nginx:1.23"),
+            ("localhost:5000/nginx", Some("1.23"))
+        );
+        assert_eq!(
+            split_image_reference("nginx@sha256:abc"),
+            ("nginx", Some("sha256:abc"))
+        );
+    }
+
+    #[test]
+    fn container_create_endpoint() {
+        let opts = ContainerCreateOpts::builder()
+            .image("nginx")
+            .name("web")
+            .build()
+            .unwrap();
+        assert_eq!(create_ep(&opts), "/containers/create?name=web");
+
+        let opts = ContainerCreateOpts::builder()
+            .image("nginx")
+            .build()
+            .unwrap();
+        assert_eq!(create_ep(&opts), "/containers/create");
+
+        let opts = ContainerCreateOpts::builder()
+            .image("nginx")
+            .name("web")
+            .platform("linux/amd64")
+            .build()
+            .unwrap();
+        assert_eq!(
+            create_ep(&opts),
+            "/containers/create?name=web&platform=linux%2Famd64"
+        );
+    }
+
+    #[tokio::test]
+    async fn container_create_invalid_platform() {
+        let docker = crate::Docker::new("tcp://127.0.0.1:80").unwrap();
+        let opts = ContainerCreateOpts::builder()
+            .image("nginx")
+            .platform("arm64")
+            .build()
+            .unwrap();
+
+        match docker.containers().create(&opts).await {
+            Err(Error::InvalidPlatform(platform)) => assert_eq!(platform, "arm64"),
+            res => panic!("Expected Error::InvalidPlatform, got {:?}", res.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn container_name_conflict() {
+        let e = name_conflict_error(
+            "web",
+            Error::Fault {
+                code: hyper::StatusCode::CONFLICT,
+                message: "Conflict.".into(),
+            },
+        );
+        match e {
+            Error::Fault { code, message } => {
+                assert_eq!(code, 409);
+                assert_eq!(
+                    message,
+                    "container name `web` is already in use - Conflict."
+                );
+            }
+            e => panic!("Expected Error::Fault, got {}", e),
+        }
+    }
+
+    #[test]
+    fn container_rename_name() {
+        let id = crate::Id::from("test");
+        assert_eq!(
+            lifecycle_ep(&id, "rename", Some(encoded_pair("name", "new-name_1.0"))),
+            "/containers/test/rename?name=new-name_1.0"
+        );
+
+        for name in ["ab", "new-name_1.0", "0container"] {
+            assert!(validate_container_name(name).is_ok(), "{}", name);
+        }
+        for name in ["", "a", "/bad", "-bad", "bad name", "bad/name", "bäd"] {
+            match validate_container_name(name) {
+                Err(Error::InvalidContainerName(invalid)) => assert_eq!(invalid, name),
+                other => panic!("Expected Error::InvalidContainerName, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn container_stats_once() {
+        let server = MockServer::start(
+            "200 OK",
+            r#"{"id":"test","memory_stats":{"usage":1000,"limit":4000}}"#,
+        )
+        .await;
+        let container = server.docker().containers().get("test");
+
+        let stats = container.stats_once().await.unwrap();
+        assert_eq!(stats.id.as_deref(), Some("test"));
+        assert_eq!(stats.memory_stats.unwrap().limit, Some(4000));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        let (path, query) = requests[0].1.split_once('?').unwrap();
+        assert_eq!(path, "/v1.41/containers/test/stats");
+        let mut params: Vec<_> = query.split('&').collect();
+        params.sort_unstable();
+        assert_eq!(params, vec!["one-shot=true", "stream=false"]);
+    }
+
+    /// Encodes `line` as a multiplexed stdout frame.
+    fn stdout_frame(line: &str) -> Vec<u8> {
+        let mut frame = vec![1, 0, 0, 0];
+        frame.extend_from_slice(&(line.len() as u32).to_be_bytes());
+        frame.extend_from_slice(line.as_bytes());
+        frame
+    }
+
+    /// Returns a raw response with the given body, the `content-length` header claims `missing`
+    /// more bytes than are sent.
+    fn raw_response(body: &[u8], missing: usize) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\nconnection: close\r\ncontent-length: {}\r\n\r\n",
+            body.len() + missing
+        )
+        .into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    #[tokio::test]
+    async fn container_logs_follow_resilient() {
+        let dropped: Vec<u8> = [
+            stdout_frame("2024-01-01T00:00:01.000000000Z one\n"),
+            stdout_frame("2024-01-01T00:00:02.000000005Z two\n"),
+            stdout_frame("2024-01-01T00:00:02.000000005Z also two\n"),
+            stdout_frame("2024-01-01T00:00:03.000000000Z thr")[..20].to_vec(),
+        ]
+        .concat();
+        let resumed: Vec<u8> = [
+            stdout_frame("2024-01-01T00:00:02.000000005Z two\n"),
+            stdout_frame("2024-01-01T00:00:02.000000005Z also two\n"),
+            stdout_frame("2024-01-01T00:00:03.000000000Z three\n"),
+        ]
+        .concat();
+        let server = MockServer::start_raw_sequence([
+            raw_response(br#"{"Config":{"Tty":false}}"#, 0),
+            raw_response(&dropped, 100),
+            raw_response(&resumed, 0),
+        ])
+        .await;
+        let container = server.docker().containers().get("test");
+
+        let opts = crate::opts::LogsOpts::builder()
+            .stdout(true)
+            .tail(crate::opts::Tail::Lines(10))
+            .build();
+        let lines: Vec<_> = container
+            .logs_follow_resilient(&opts)
+            .map_ok(|chunk| String::from_utf8(chunk_output(chunk).1).unwrap())
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                "2024-01-01T00:00:01.000000000Z one\n",
+                "2024-01-01T00:00:02.000000005Z two\n",
+                "2024-01-01T00:00:02.000000005Z also two\n",
+                "2024-01-01T00:00:03.000000000Z three\n",
+            ]
+        );
+
+        let paths: Vec<_> = server
+            .requests()
+            .into_iter()
+            .map(|(_, path)| path)
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/v1.41/containers/test/json",
+                "/v1.41/containers/test/logs?stdout=true&tail=10&follow=true&timestamps=true",
+                "/v1.41/containers/test/logs?stdout=true&follow=true&timestamps=true\
+                 &since=1704067202.000000005",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn container_logs_known_tty() {
+        let server = MockServer::start_raw_sequence([raw_response(b"raw tty output", 0)]).await;
+        let container = server.docker().containers().get("test");
+
+        let opts = crate::opts::LogsOpts::builder()
+            .stdout(true)
+            .tty(true)
+            .build();
+        assert_eq!(opts.serialize().as_deref(), Some("stdout=true"));
+        let chunks: Vec<_> = container
+            .logs(&opts)
+            .map_ok(chunk_output)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(chunks, vec![("stdout", b"raw tty output".to_vec())]);
+
+        // the container isn't inspected if the TTY mode is known
+        assert_eq!(
+            server.requests(),
+            vec![(
+                "GET".to_string(),
+                "/v1.41/containers/test/logs?stdout=true".to_string()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn container_remove() {
+        let server = MockServer::start("204 No Content", "").await;
+        let container = server.docker().containers().get("test");
+
+        container
+            .remove(
+                &ContainerRemoveOpts::builder()
+                    .force(true)
+                    .volumes(true)
+                    .build(),
+            )
+            .await
+            .unwrap();
+        container.delete().await.unwrap();
+        assert_eq!(
+            server.requests(),
+            vec![
+                (
+                    "DELETE".to_string(),
+                    "/v1.41/containers/test?force=true&v=true".to_string()
+                ),
+                ("DELETE".to_string(), "/v1.41/containers/test".to_string()),
+            ]
+        );
+
+        let server = MockServer::start(
+            "409 Conflict",
+            r#"{"message":"cannot remove container \"/test\": container is running"}"#,
+        )
+        .await;
+        let container = server.docker().containers().get("test");
+        match container.remove(&ContainerRemoveOpts::default()).await {
+            Err(Error::Fault { code, message }) => {
+                assert_eq!(code, hyper::StatusCode::CONFLICT);
+                assert!(message.contains("`force`"), "{}", message);
+            }
+            res => panic!("Expected Error::Fault, got {:?}", res),
+        }
+    }
+
+    #[tokio::test]
+    async fn container_stop_and_remove() {
+        let server =
+            MockServer::start_sequence([("204 No Content", ""), ("204 No Content", "")]).await;
+        let container = server.docker().containers().get("test");
+
+        container
+            .stop_and_remove(Some(Duration::from_secs(5)), true)
+            .await
+            .unwrap();
+        assert_eq!(
+            server.requests(),
+            vec![
+                (
+                    "POST".to_string(),
+                    "/v1.41/containers/test/stop?t=5".to_string()
+                ),
+                (
+                    "DELETE".to_string(),
+                    "/v1.41/containers/test?force=true&v=true".to_string()
+                ),
+            ]
+        );
+
+        // an already stopped container is still removed
+        let server =
+            MockServer::start_sequence([("304 Not Modified", ""), ("204 No Content", "")]).await;
+        let container = server.docker().containers().get("test");
+        container.stop_and_remove(None, false).await.unwrap();
+        assert_eq!(
+            server.requests(),
+            vec![
+                (
+                    "POST".to_string(),
+                    "/v1.41/containers/test/stop".to_string()
+                ),
+                (
+                    "DELETE".to_string(),
+                    "/v1.41/containers/test?force=true&v=false".to_string()
+                ),
+            ]
+        );
+
+        // removal is not attempted if stopping fails
+        let server = MockServer::start("404 Not Found", r#"{"message":"No such container"}"#).await;
+        let container = server.docker().containers().get("test");
+        assert!(container.stop_and_remove(None, false).await.is_err());
+        assert_eq!(server.requests().len(), 1);
+    }
 }
@@ -4,7 +4,6 @@ use futures_util::{
     stream::{Stream, TryStreamExt},
     TryFutureExt,
 };
-use hyper::Body;
 
 use crate::{
     conn::{tty, Headers, Payload},
@@ -12,6 +11,7 @@ use crate::{
     opts::{ExecCreateOpts, ExecResizeOpts},
     Docker, Result,
 };
+use containers_api::url::construct_ep;
 
 api_doc! { Exec
 /// Interface for docker exec instance
@@ -50,6 +50,7 @@ impl Exec {
             id: String,
         }
 
+        opts.validate()?;
         docker
             .post_json(
                 &format!("/containers/{}/exec", container_id.as_ref()),
@@ -88,7 +89,7 @@ impl Exec {
         // To not tie the lifetime of `opts` to the stream, we do the serializing work outside of
         // the stream. But for backwards compatability, we have to return the error inside of the
         // stream.
-        let body_result = opts.serialize();
+        let body_result = opts.validate().and_then(|_| opts.serialize());
 
         // To not tie the lifetime of `container_id` to the stream, we convert it to an (owned)
         // endpoint outside of the stream.
@@ -162,14 +163,11 @@ impl Exec {
     /// Resize the TTY session used by an exec instance. This only works if the exec was created
     /// with `tty` enabled.
     pub async fn resize(&self, opts: &ExecResizeOpts) -> Result<()> {
-        let body: Body = opts.serialize()?.into();
+        let ep = construct_ep(format!("/exec/{}/resize", &self.id), opts.serialize());
 
         self.docker
-            .post_json(
-                &format!("/exec/{}/resize", &self.id),
-                Payload::Json(body),
-                Headers::none(),
-            )
+            .post_string(&ep, Payload::empty(), Headers::none())
             .await
+            .map(|_| ())
     }}
 }
@@ -4,21 +4,25 @@ use crate::{
     models,
     opts::{
         ClearCacheOpts, ImageBuildOpts, ImageListOpts, ImagePruneOpts, ImagePushOpts,
-        ImageRemoveOpts, PullOpts, TagOpts,
+        ImageRemoveOpts, ImageSearchOpts, PullOpts, TagOpts,
     },
 };
 
 use std::io::Read;
 
-use futures_util::{stream::Stream, TryFutureExt, TryStreamExt};
+use futures_util::{stream::Stream, StreamExt, TryFutureExt, TryStreamExt};
+use hyper::body::Bytes;
 
 use containers_api::{
     conn::{Headers, Payload, AUTH_HEADER},
     tarball,
-    url::{construct_ep, encoded_pair, encoded_pairs},
+    url::{construct_ep, encoded_pairs},
 };
 
-use crate::Result;
+use crate::{Error, Result};
+
+mod context;
+pub use context::BuildContext;
 
 impl_api_ty!(Image => name);
 
@@ -27,6 +31,17 @@ impl Image {
         Inspect -> &format!("/images/{}/json", img.name), models::ImageInspect
     }
 
+    api_doc! { Image => Inspect
+    |
+    /// Same as [`inspect`](Image::inspect) but returns [`ImageDetails`](models::ImageDetails)
+    /// with helpers for commonly used values, keeping fields unknown to this version of the
+    /// library.
+    pub async fn details(&self) -> Result<models::ImageDetails> {
+        self.docker
+            .get_json(&format!("/images/{}/json", self.name))
+            .await
+    }}
+
     api_doc! { Image => Delete
     |
     /// Remove this image with options.
@@ -40,9 +55,9 @@ impl Image {
 
     api_doc! { Image => Delete
     |
-    /// Delete this image with force.
+    /// Delete this image without any options, this fails if the image is used by a container.
     ///
-    /// Use [`remove`](Image::remove) to delete with options.
+    /// Use [`remove`](Image::remove) to delete with options like `force`.
     pub async fn delete(&self) -> Result<Vec<models::ImageDeleteResponseItem>> {
         self.docker
             .delete_json(&format!("/images/{}", self.name))
@@ -82,19 +97,15 @@ impl Image {
 
     api_doc! { Image => Push
     |
-    /// Push an image to registry.
-    pub async fn push(&self, opts: &ImagePushOpts) -> Result<()> {
-        let ep = construct_ep(format!("/images/{}/push", self.name), opts.serialize());
-
-        let headers = opts
-            .auth_header()
-            .map(|auth| Headers::single(AUTH_HEADER, auth))
-            .unwrap_or_else(Headers::default);
-
-        self.docker
-            .post_string(&ep, Payload::empty(), Some(headers))
-            .await
-            .map(|_| ())
+    /// Push an image to registry returning a stream of progress updates.
+    ///
+    /// Errors reported by the registry while pushing, like failed authentication, are returned
+    /// as `Err` items of the stream.
+    pub fn push<'docker>(
+        &'docker self,
+        opts: &ImagePushOpts,
+    ) -> impl Stream<Item = Result<models::ImageBuildChunk>> + Unpin + 'docker {
+        push_stream(&self.docker, &self.name, opts)
     }}
 
     api_doc! { Distribution => Inspect
@@ -147,6 +158,49 @@ impl Images {
         )
     }}
 
+    api_doc! { Image => Build
+    |
+    /// Builds a new image from a [`BuildContext`](BuildContext), for example created from a
+    /// directory with [`BuildContext::from_dir`](BuildContext::from_dir). The context is streamed
+    /// to the docker host while it is created. The `path` of `opts` is ignored.
+    pub fn build_with_context<'docker>(
+        &'docker self,
+        opts: &ImageBuildOpts,
+        context: BuildContext,
+    ) -> impl Stream<Item = Result<models::ImageBuildChunk>> + Unpin + 'docker {
+        let ep = construct_ep("/build", opts.serialize());
+
+        Box::pin(self.docker.post_into_stream(
+            ep,
+            Payload::Tar(hyper::Body::wrap_stream(context)),
+            Headers::none(),
+        ))
+    }}
+
+    api_doc! { Image => Build
+    |
+    /// Builds a new image from an already prepared build context. The `tarball` must contain the
+    /// Dockerfile and may be compressed with gzip, bzip2 or xz. Its chunks are sent to the docker
+    /// host as they are produced, so a large tarball can be streamed from a file without reading
+    /// it into memory first. The `path` of `opts` is ignored.
+    pub fn build_tar<'docker, S, E>(
+        &'docker self,
+        opts: &ImageBuildOpts,
+        tarball: S,
+    ) -> impl Stream<Item = Result<models::ImageBuildChunk>> + Unpin + 'docker
+    where
+        S: Stream<Item = std::result::Result<Bytes, E>> + Send + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    {
+        let ep = construct_ep("/build", opts.serialize());
+
+        Box::pin(self.docker.post_into_stream(
+            ep,
+            Payload::Tar(hyper::Body::wrap_stream(tarball)),
+            Headers::none(),
+        ))
+    }}
+
     api_doc! { Image => Search
     |
     /// Search for docker images by term.
@@ -154,11 +208,19 @@ impl Images {
     where
         T: AsRef<str>,
     {
+        self.search_with(&ImageSearchOpts::builder().term(term.as_ref()).build())
+            .await
+    }}
+
+    api_doc! { Image => Search
+    |
+    /// Search for docker images with a limit on the number of results and optional filters.
+    pub async fn search_with(
+        &self,
+        opts: &ImageSearchOpts,
+    ) -> Result<models::ImageSearch200Response> {
         self.docker
-            .get_json(&construct_ep(
-                "/images/search",
-                Some(encoded_pair("term", term.as_ref())),
-            ))
+            .get_json(&construct_ep("/images/search", opts.serialize()))
             .await
     }}
 
@@ -169,13 +231,21 @@ impl Images {
         &'docker self,
         opts: &PullOpts,
     ) -> impl Stream<Item = Result<models::ImageBuildChunk>> + Unpin + 'docker {
+        if let Err(e) = opts.validate() {
+            return Box::pin(futures_util::stream::once(async { Err(e) }).left_stream());
+        }
+
         let headers = opts.auth_header().map(|a| Headers::single(AUTH_HEADER, a));
 
-        Box::pin(self.docker.post_into_stream(
-            construct_ep("/images/create", opts.serialize()),
-            Payload::empty(),
-            headers,
-        ))
+        Box::pin(
+            self.docker
+                .post_into_stream(
+                    construct_ep("/images/create", opts.serialize()),
+                    Payload::empty(),
+                    headers,
+                )
+                .right_stream(),
+        )
     }}
 
     api_doc! { Image => GetAll
@@ -224,10 +294,16 @@ impl Images {
 
     api_doc! { Image => Push
     |
-    /// Push an image to registry.
-    pub async fn push(&self, name: impl Into<crate::Id>, opts: &ImagePushOpts) -> Result<()> {
-        let image = Image::new(self.docker.clone(), name);
-        image.push(opts).await
+    /// Push an image to registry returning a stream of progress updates.
+    ///
+    /// Errors reported by the registry while pushing, like failed authentication, are returned
+    /// as `Err` items of the stream.
+    pub fn push<'docker>(
+        &'docker self,
+        name: impl Into<crate::Id>,
+        opts: &ImagePushOpts,
+    ) -> impl Stream<Item = Result<models::ImageBuildChunk>> + Unpin + 'docker {
+        push_stream(&self.docker, &name.into(), opts)
     }}
 
     api_doc! { Build => Prune
@@ -246,3 +322,125 @@ impl Images {
             .await
     }}
 }
+
+fn push_stream<'docker>(
+    docker: &'docker crate::Docker,
+    name: &crate::Id,
+    opts: &ImagePushOpts,
+) -> impl Stream<Item = Result<models::ImageBuildChunk>> + Unpin + 'docker {
+    let ep = construct_ep(format!("/images/{}/push", name), opts.serialize());
+
+    let headers = opts
+        .auth_header()
+        .map(|auth| Headers::single(AUTH_HEADER, auth))
+        .unwrap_or_default();
+
+    Box::pin(
+        docker
+            .post_into_stream(ep, Payload::empty(), Some(headers))
+            .and_then(|chunk| async move {
+                match chunk {
+                    models::ImageBuildChunk::Error { error_detail, .. } => {
+                        Err(Error::StringError(error_detail.message))
+                    }
+                    chunk => Ok(chunk),
+                }
+            }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::{ImageSearch200Response, ImageSummary},
+        opts::ImageBuildOpts,
+        testutil::MockServer,
+    };
+
+    use futures_util::TryStreamExt;
+    use hyper::body::Bytes;
+
+    #[tokio::test]
+    async fn image_build_tar_streams_body() {
+        let server = MockServer::start("200 OK", "{\"stream\":\"Step 1/1\"}\n").await;
+        let opts = ImageBuildOpts::builder("").tag("test").build();
+        let tarball = futures_util::stream::iter([
+            Ok::<_, std::io::Error>(Bytes::from_static(b"first ")),
+            Ok(Bytes::from_static(b"second")),
+        ]);
+
+        let chunks: Vec<_> = server
+            .docker()
+            .images()
+            .build_tar(&opts, tarball)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(chunks.len(), 1);
+
+        let request = &server.recorded_requests()[0];
+        assert_eq!(request.method, "POST");
+        assert!(request.path.starts_with("/v1.41/build?"));
+        assert_eq!(request.header("content-type"), Some("application/tar"));
+        assert_eq!(request.body, b"first second");
+    }
+
+    #[test]
+    fn image_list_decode() {
+        let payload = r#"[{
+            "Id": "sha256:e216a057b1cb1efc11f8a268f37ef62083e70b1b38323ba252e25ac88904a7e8",
+            "ParentId": "",
+            "RepoTags": ["alpine:3.16", "alpine:latest"],
+            "RepoDigests": [
+                "alpine@sha256:bc41182d7ef5ffc53a40b044e725193bc10142a1243f395ee852a8d9730fc2ad"
+            ],
+            "Created": 1660000000,
+            "Size": 5543522,
+            "SharedSize": -1,
+            "VirtualSize": 5543522,
+            "Labels": {"maintainer": "someone"},
+            "Containers": -1
+        }]"#;
+
+        let images: Vec<ImageSummary> = serde_json::from_str(payload).unwrap();
+        assert_eq!(images.len(), 1);
+
+        let image = &images[0];
+        assert!(image.id.starts_with("sha256:e216a057"));
+        assert_eq!(image.repo_tags, vec!["alpine:3.16", "alpine:latest"]);
+        assert_eq!(image.repo_digests.len(), 1);
+        assert_eq!(image.created, 1660000000);
+        assert_eq!(image.size, 5543522);
+        assert_eq!(image.virtual_size, 5543522);
+        assert_eq!(image.labels["maintainer"], "someone");
+    }
+
+    #[test]
+    fn image_search_decode() {
+        let payload = r#"[
+            {
+                "description": "Official build of Nginx.",
+                "is_official": true,
+                "is_automated": false,
+                "name": "nginx",
+                "star_count": 18000
+            },
+            {
+                "description": "",
+                "is_official": false,
+                "is_automated": true,
+                "name": "someone/nginx",
+                "star_count": 3
+            }
+        ]"#;
+
+        let results: ImageSearch200Response = serde_json::from_str(payload).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name.as_deref(), Some("nginx"));
+        assert_eq!(results[0].is_official, Some(true));
+        assert_eq!(results[0].is_automated, Some(false));
+        assert_eq!(results[0].star_count, Some(18000));
+        assert_eq!(results[1].is_official, Some(false));
+        assert_eq!(results[1].is_automated, Some(true));
+    }
+}
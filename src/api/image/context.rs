@@ -0,0 +1,383 @@
+//! Build contexts streamed to the docker host when building images.
+use crate::{Error, Result};
+
+use futures_util::{io::AsyncRead, ready, Stream};
+use hyper::body::Bytes;
+use tokio::sync::mpsc;
+
+use std::{
+    fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Size of the chunks in which the tarball is sent.
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Number of chunks buffered before creating the tarball waits for them to be read.
+const CHUNK_BUFFER: usize = 16;
+/// Files that are always part of the build context, even if excluded in `.dockerignore`.
+const ALWAYS_INCLUDED: [&str; 2] = ["Dockerfile", ".dockerignore"];
+
+/// A tarball of a build context that is created while it is read, so that the files are never
+/// loaded into memory at once. Use
+/// [`Images::build_with_context`](crate::Images::build_with_context) to build an image from it.
+///
+/// The context implements [`AsyncRead`] as well as [`Stream`] of byte chunks.
+pub struct BuildContext {
+    chunks: mpsc::Receiver<io::Result<Bytes>>,
+    current: Bytes,
+}
+
+impl BuildContext {
+    /// Creates a build context of all files in the directory at `path`, skipping the files
+    /// excluded by the `.dockerignore` file in this directory if there is one. Symbolic links are
+    /// added as links without following them. `Dockerfile` and `.dockerignore` are always added,
+    /// like the docker CLI does.
+    ///
+    /// The tarball is created on a separate thread, errors encountered while walking the
+    /// directory are returned when reading the context.
+    pub fn from_dir(path: impl AsRef<Path>) -> Result<BuildContext> {
+        let root = path.as_ref().to_path_buf();
+        if !root.is_dir() {
+            return Err(Error::StringError(format!(
+                "build context `{}` is not a directory",
+                root.display()
+            )));
+        }
+        let ignore = match fs::read_to_string(root.join(".dockerignore")) {
+            Ok(contents) => DockerIgnore::parse(&contents),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => DockerIgnore::default(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let (tx, chunks) = mpsc::channel(CHUNK_BUFFER);
+        std::thread::Builder::new()
+            .name("docker-build-context".into())
+            .spawn(move || {
+                let errors = tx.clone();
+                if let Err(e) = write_tarball(&root, &ignore, ChunkWriter(tx)) {
+                    // the context was dropped if sending fails, nobody is left to report to
+                    let _ = errors.blocking_send(Err(e));
+                }
+            })?;
+
+        Ok(BuildContext {
+            chunks,
+            current: Bytes::new(),
+        })
+    }
+}
+
+impl std::fmt::Debug for BuildContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BuildContext").finish_non_exhaustive()
+    }
+}
+
+impl Stream for BuildContext {
+    type Item = io::Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if !this.current.is_empty() {
+            return Poll::Ready(Some(Ok(std::mem::take(&mut this.current))));
+        }
+        this.chunks.poll_recv(cx)
+    }
+}
+
+impl AsyncRead for BuildContext {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        while this.current.is_empty() {
+            match ready!(this.chunks.poll_recv(cx)) {
+                Some(Ok(chunk)) => this.current = chunk,
+                Some(Err(e)) => return Poll::Ready(Err(e)),
+                None => return Poll::Ready(Ok(0)),
+            }
+        }
+
+        let n = buf.len().min(this.current.len());
+        buf[..n].copy_from_slice(&this.current.split_to(n));
+        Poll::Ready(Ok(n))
+    }
+}
+
+/// Sends everything written to it as chunks to a [`BuildContext`].
+struct ChunkWriter(mpsc::Sender<io::Result<Bytes>>);
+
+impl Write for ChunkWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .blocking_send(Ok(Bytes::copy_from_slice(buf)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "build context was dropped"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn write_tarball(root: &Path, ignore: &DockerIgnore, writer: ChunkWriter) -> io::Result<()> {
+    let mut tar = tar::Builder::new(BufWriter::with_capacity(CHUNK_SIZE, writer));
+    tar.follow_symlinks(false);
+    append_dir(&mut tar, root, Path::new(""), ignore)?;
+    tar.into_inner()?.flush()
+}
+
+fn append_dir<W: Write>(
+    tar: &mut tar::Builder<W>,
+    root: &Path,
+    dir: &Path,
+    ignore: &DockerIgnore,
+) -> io::Result<()> {
+    let mut entries = fs::read_dir(root.join(dir))?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = dir.join(entry.file_name());
+        let excluded = ignore.is_excluded(&path)
+            && !(dir.as_os_str().is_empty()
+                && ALWAYS_INCLUDED
+                    .iter()
+                    .any(|name| entry.file_name() == *name));
+        let is_dir = entry.file_type()?.is_dir();
+
+        if !excluded {
+            tar.append_path_with_name(entry.path(), &path)?;
+        }
+        // files in an excluded directory may be included again by a negated pattern
+        if is_dir && (!excluded || ignore.has_negations()) {
+            append_dir(tar, root, &path, ignore)?;
+        }
+    }
+    Ok(())
+}
+
+/// Patterns of a `.dockerignore` file.
+#[derive(Debug, Default)]
+struct DockerIgnore {
+    patterns: Vec<IgnorePattern>,
+}
+
+#[derive(Debug)]
+struct IgnorePattern {
+    segments: Vec<String>,
+    negated: bool,
+}
+
+impl DockerIgnore {
+    fn parse(contents: &str) -> Self {
+        let patterns = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (negated, pattern) = match line.strip_prefix('!') {
+                    Some(pattern) => (true, pattern.trim()),
+                    None => (false, line),
+                };
+                let segments: Vec<_> = pattern
+                    .split('/')
+                    .filter(|segment| !segment.is_empty() && *segment != ".")
+                    .map(ToOwned::to_owned)
+                    .collect();
+                (!segments.is_empty()).then_some(IgnorePattern { segments, negated })
+            })
+            .collect();
+
+        Self { patterns }
+    }
+
+    fn has_negations(&self) -> bool {
+        self.patterns.iter().any(|pattern| pattern.negated)
+    }
+
+    /// Returns whether `path`, relative to the root of the context, is excluded. The last
+    /// pattern matching the path or one of its parent directories decides.
+    fn is_excluded(&self, path: &Path) -> bool {
+        let segments: Vec<_> = path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect();
+        let segments: Vec<&str> = segments.iter().map(AsRef::as_ref).collect();
+
+        self.patterns
+            .iter()
+            .rev()
+            .find(|pattern| {
+                (1..=segments.len()).any(|n| match_segments(&pattern.segments, &segments[..n]))
+            })
+            .map(|pattern| !pattern.negated)
+            .unwrap_or(false)
+    }
+}
+
+/// Matches path segments against pattern segments where `**` matches any number of segments.
+fn match_segments(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| match_segments(rest, &path[skip..]))
+        }
+        Some((first, rest)) => match path.split_first() {
+            Some((segment, path)) => match_glob(first, segment) && match_segments(rest, path),
+            None => false,
+        },
+    }
+}
+
+/// Matches a single path segment against a glob supporting `*`, `?`, `[...]` classes and `\`
+/// escapes.
+fn match_glob(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    match_glob_chars(&pattern, &name)
+}
+
+fn match_glob_chars(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| match_glob_chars(rest, &name[skip..])),
+        Some(('?', rest)) => !name.is_empty() && match_glob_chars(rest, &name[1..]),
+        Some(('[', rest)) => match name.split_first() {
+            Some((c, name)) => match match_class(rest, *c) {
+                Some((true, rest)) => match_glob_chars(rest, name),
+                _ => false,
+            },
+            None => false,
+        },
+        Some(('\\', rest)) if !rest.is_empty() => {
+            name.first() == rest.first() && match_glob_chars(&rest[1..], &name[1..])
+        }
+        Some((c, rest)) => name.first() == Some(c) && match_glob_chars(rest, &name[1..]),
+    }
+}
+
+/// Matches `c` against a character class like `[a-z]` or `[^0-9]` following the opening bracket.
+/// Returns whether it matches and the rest of the pattern after the closing bracket, or `None`
+/// if the class is not closed.
+fn match_class(pattern: &[char], c: char) -> Option<(bool, &[char])> {
+    let (negated, pattern) = match pattern.split_first() {
+        Some(('^' | '!', rest)) => (true, rest),
+        _ => (false, pattern),
+    };
+    let end = pattern.iter().position(|c| *c == ']')?;
+    let (mut class, rest) = (&pattern[..end], &pattern[end + 1..]);
+
+    let mut found = false;
+    while !class.is_empty() {
+        if class.len() > 2 && class[1] == '-' {
+            found |= (class[0]..=class[2]).contains(&c);
+            class = &class[3..];
+        } else {
+            found |= class[0] == c;
+            class = &class[1..];
+        }
+    }
+    Some((found != negated, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::io::AsyncReadExt;
+    use std::path::PathBuf;
+
+    fn excluded(ignore: &str, path: &str) -> bool {
+        DockerIgnore::parse(ignore).is_excluded(&PathBuf::from(path))
+    }
+
+    #[test]
+    fn dockerignore_patterns() {
+        assert!(excluded("secret.txt", "secret.txt"));
+        assert!(!excluded("secret.txt", "src/secret.txt"));
+        assert!(excluded("/target/", "target/debug/app"));
+        assert!(excluded("*.log", "build.log"));
+        assert!(!excluded("*.log", "logs/build.log"));
+        assert!(excluded("**/*.log", "logs/build.log"));
+        assert!(excluded("**/*.log", "build.log"));
+        assert!(excluded("docs/**/draft-?.md", "docs/2022/draft-1.md"));
+        assert!(excluded("[a-c]*.tmp", "b1.tmp"));
+        assert!(!excluded("[^a-c]*.tmp", "b1.tmp"));
+        assert!(excluded(
+            "# comment\n\n*.log\n!keep.log\nkeep.log",
+            "keep.log"
+        ));
+        assert!(!excluded("*.log\n!keep.log", "keep.log"));
+        assert!(excluded("*.log\n!keep.log", "other.log"));
+        assert!(!excluded("", "anything"));
+    }
+
+    #[tokio::test]
+    async fn build_context_from_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("Dockerfile"), "FROM scratch\nCOPY . /\n").unwrap();
+        fs::write(root.join(".dockerignore"), "secret.txt\n*.log\n!keep.log\n").unwrap();
+        fs::write(root.join("secret.txt"), "password").unwrap();
+        fs::write(root.join("build.log"), "").unwrap();
+        fs::write(root.join("keep.log"), "").unwrap();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src").join("main.rs"), "fn main() {}").unwrap();
+        // larger than a single chunk
+        let large = vec![7u8; CHUNK_SIZE * 3 + 1];
+        fs::write(root.join("src").join("data.bin"), &large).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("main.rs", root.join("src").join("link.rs")).unwrap();
+
+        let mut tarball = Vec::new();
+        BuildContext::from_dir(root)
+            .unwrap()
+            .read_to_end(&mut tarball)
+            .await
+            .unwrap();
+
+        let mut archive = tar::Archive::new(tarball.as_slice());
+        let mut paths = Vec::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().to_string_lossy().into_owned();
+            if path == "src/data.bin" {
+                let mut contents = Vec::new();
+                io::Read::read_to_end(&mut entry, &mut contents).unwrap();
+                assert_eq!(contents, large);
+            }
+            if path == "src/link.rs" {
+                assert!(entry.header().entry_type().is_symlink());
+                assert_eq!(
+                    entry.link_name().unwrap().unwrap(),
+                    PathBuf::from("main.rs")
+                );
+            }
+            paths.push(path);
+        }
+
+        assert!(!paths.contains(&"secret.txt".to_string()));
+        assert!(!paths.contains(&"build.log".to_string()));
+        for path in [
+            ".dockerignore",
+            "Dockerfile",
+            "keep.log",
+            "src/data.bin",
+            "src/main.rs",
+        ] {
+            assert!(paths.contains(&path.to_string()), "{} is missing", path);
+        }
+        #[cfg(unix)]
+        assert!(paths.contains(&"src/link.rs".to_string()));
+    }
+
+    #[test]
+    fn build_context_from_missing_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(BuildContext::from_dir(dir.path().join("missing")).is_err());
+    }
+}
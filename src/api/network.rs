@@ -7,17 +7,37 @@ use crate::{
         ContainerConnectionOpts, ContainerDisconnectionOpts, NetworkCreateOpts, NetworkListOpts,
         NetworkPruneOpts,
     },
-    Result,
+    Error, Result,
 };
 
+use serde::Deserialize;
+
+use std::collections::HashMap;
+
 impl_api_ty!(Network => id);
 
+#[derive(Deserialize)]
+/// Subset of the network inspect response listing the connected containers.
+struct NetworkContainers {
+    #[serde(rename = "Containers")]
+    containers: Option<HashMap<String, models::NetworkContainerDetails>>,
+}
+
 impl Network {
     impl_api_ep! { net: Network, resp
         Inspect -> &format!("/networks/{}", net.id), models::Network
         Delete -> &format!("/networks/{}", net.id), ()
     }
 
+    api_doc! { Network => Inspect
+    |
+    /// Same as [`inspect`](Network::inspect) but returns
+    /// [`NetworkDetails`](models::NetworkDetails) with the scope and driver as typed values,
+    /// keeping fields unknown to this version of the library.
+    pub async fn details(&self) -> Result<models::NetworkDetails> {
+        self.docker.get_json(&format!("/networks/{}", self.id)).await
+    }}
+
     api_doc! { Network => Connect
     |
     /// Connect a container to a network.
@@ -32,6 +52,43 @@ impl Network {
             .map(|_| ())
     }}
 
+    /// Connect a container to a network like [`Network::connect`](Network::connect) and return
+    /// the endpoint created for it, including the assigned addresses.
+    ///
+    /// The endpoint is looked up by inspecting the network after connecting the container. A
+    /// container with the exact name wins over ID prefixes, an ID prefix matching more than one
+    /// container is an error.
+    pub async fn connect_inspect(
+        &self,
+        opts: &ContainerConnectionOpts,
+    ) -> Result<models::NetworkContainerDetails> {
+        self.connect(opts).await?;
+
+        let container = opts.container();
+        let containers = self.containers().await?;
+        if let Some(details) = containers
+            .values()
+            .find(|details| details.name == container)
+        {
+            return Ok(details.clone());
+        }
+
+        let mut matches = containers
+            .into_iter()
+            .filter(|(id, _)| id.starts_with(container));
+        match (matches.next(), matches.next()) {
+            (Some((_, details)), None) => Ok(details),
+            (Some(_), Some(_)) => Err(Error::InvalidResponse(format!(
+                "container ID prefix `{}` matches more than one container in network `{}`",
+                container, self.id
+            ))),
+            _ => Err(Error::InvalidResponse(format!(
+                "container `{}` is not listed in network `{}`",
+                container, self.id
+            ))),
+        }
+    }
+
     api_doc! { Network => Disconnect
     |
     /// Disconnect a container from a network.
@@ -45,6 +102,41 @@ impl Network {
             .await
             .map(|_| ())
     }}
+
+    /// Disconnect all containers attached to this network, for example before deleting it.
+    /// Returns the IDs of the disconnected containers sorted alphabetically.
+    ///
+    /// Stops at the first container that fails to disconnect, containers disconnected before
+    /// stay disconnected.
+    pub async fn disconnect_all(&self, force: bool) -> Result<Vec<String>> {
+        let mut ids: Vec<_> = self.containers().await?.into_keys().collect();
+        ids.sort();
+
+        for id in &ids {
+            let opts = ContainerDisconnectionOpts::builder(id).force(force).build();
+            self.disconnect(&opts).await?;
+        }
+        Ok(ids)
+    }
+
+    /// Force disconnect all containers attached to this network with
+    /// [`Network::disconnect_all`](Network::disconnect_all), then delete it. Returns the IDs of
+    /// the disconnected containers.
+    ///
+    /// The network is not deleted if disconnecting any of the containers fails.
+    pub async fn delete_force(&self) -> Result<Vec<String>> {
+        let ids = self.disconnect_all(true).await?;
+        self.delete().await?;
+        Ok(ids)
+    }
+
+    /// Returns the endpoints of the containers attached to this network keyed by container ID.
+    async fn containers(&self) -> Result<HashMap<String, models::NetworkContainerDetails>> {
+        self.docker
+            .get_json(&format!("/networks/{}", self.id))
+            .await
+            .map(|network: NetworkContainers| network.containers.unwrap_or_default())
+    }
 }
 
 impl Networks {
@@ -55,9 +147,31 @@ impl Networks {
 
     api_doc! { Network => Create
     |
-    /// Create a new network.
+    /// Create a new network. Warnings reported by the docker host are logged, use
+    /// [`create_strict`](Networks::create_strict) to treat them as errors.
     pub async fn create(&self, opts: &NetworkCreateOpts) -> Result<Network> {
-        // #TODO: handle missing id and return warnings (?)
+        let info = self.create_info(opts).await?;
+        for warning in info.warnings() {
+            log::warn!("network `{}` created with a warning - {}", info.id, warning);
+        }
+        Ok(Network::new(self.docker.clone(), info.id))
+    }}
+
+    api_doc! { Network => Create
+    |
+    /// Create a new network returning [`Error::Warnings`] if the docker host reported any
+    /// warnings. The network is created either way and has to be removed by the caller if
+    /// it's not wanted.
+    pub async fn create_strict(&self, opts: &NetworkCreateOpts) -> Result<Network> {
+        let info = self.create_info(opts).await?;
+        let warnings = info.warnings();
+        if !warnings.is_empty() {
+            return Err(Error::Warnings(warnings.into_iter().map(ToOwned::to_owned).collect()));
+        }
+        Ok(Network::new(self.docker.clone(), info.id))
+    }}
+
+    async fn create_info(&self, opts: &NetworkCreateOpts) -> Result<models::NetworkCreateInfo> {
         self.docker
             .post_json(
                 "/networks/create",
@@ -65,8 +179,289 @@ impl Networks {
                 Headers::none(),
             )
             .await
-            .map(|resp: models::NetworkCreate201Response| {
-                Network::new(self.docker.clone(), resp.id.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{opts::NetworkFilter, testutil::MockServer};
+
+    #[tokio::test]
+    async fn network_connect_inspect() {
+        let server = MockServer::start_sequence(vec![
+            ("200 OK", ""),
+            (
+                "200 OK",
+                r#"{"Id":"22be93d5babb","Containers":{"19a4d5d687db":{"Name":"web","EndpointID":"628cadb8bcb9","MacAddress":"02:42:ac:13:00:02","IPv4Address":"172.19.0.2/16","IPv6Address":""}}}"#,
+            ),
+        ])
+        .await;
+        let network = server.docker().networks().get("22be93d5babb");
+
+        let opts = ContainerConnectionOpts::builder("web").build();
+        let details = network.connect_inspect(&opts).await.unwrap();
+        assert!(!details.ipv4_address.is_empty());
+        assert_eq!(details.ipv4_address, "172.19.0.2/16");
+        assert_eq!(details.endpoint_id, "628cadb8bcb9");
+        assert_eq!(details.mac_address, "02:42:ac:13:00:02");
+        assert_eq!(
+            server.requests(),
+            vec![
+                (
+                    "POST".to_string(),
+                    "/v1.41/networks/22be93d5babb/connect".to_string()
+                ),
+                (
+                    "GET".to_string(),
+                    "/v1.41/networks/22be93d5babb".to_string()
+                ),
+            ]
+        );
+
+        let opts = ContainerConnectionOpts::builder("19a4d5d6").build();
+        let details = network.connect_inspect(&opts).await.unwrap();
+        assert_eq!(details.name, "web");
+
+        let opts = ContainerConnectionOpts::builder("db").build();
+        assert!(matches!(
+            network.connect_inspect(&opts).await,
+            Err(Error::InvalidResponse(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn network_connect_inspect_ambiguous() {
+        let network = r#"{"Id":"22be93d5babb","Containers":{"ab12":{"Name":"web","IPv4Address":"172.19.0.2/16"},"ab34":{"Name":"ab","IPv4Address":"172.19.0.3/16"}}}"#;
+        let server = MockServer::start_sequence(vec![
+            ("200 OK", ""),
+            ("200 OK", network),
+            ("200 OK", ""),
+            ("200 OK", network),
+            ("200 OK", ""),
+            ("200 OK", network),
+        ])
+        .await;
+        let network = server.docker().networks().get("22be93d5babb");
+
+        let opts = ContainerConnectionOpts::builder("ab").build();
+        let details = network.connect_inspect(&opts).await.unwrap();
+        assert_eq!(details.ipv4_address, "172.19.0.3/16");
+
+        let opts = ContainerConnectionOpts::builder("ab1").build();
+        let details = network.connect_inspect(&opts).await.unwrap();
+        assert_eq!(details.name, "web");
+
+        let opts = ContainerConnectionOpts::builder("a").build();
+        assert!(matches!(
+            network.connect_inspect(&opts).await,
+            Err(Error::InvalidResponse(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn network_details() {
+        let server = MockServer::start(
+            "200 OK",
+            r#"{"Id":"22be93d5babb","Scope":"local","Driver":"bridge","ConfigOnly":false}"#,
+        )
+        .await;
+        let details = server
+            .docker()
+            .networks()
+            .get("22be93d5babb")
+            .details()
+            .await
+            .unwrap();
+        assert_eq!(details.id.as_deref(), Some("22be93d5babb"));
+        assert_eq!(details.scope(), Some(crate::opts::Scope::Local));
+        assert_eq!(details.extra["ConfigOnly"], false);
+        assert_eq!(
+            server.requests(),
+            vec![(
+                "GET".to_string(),
+                "/v1.41/networks/22be93d5babb".to_string()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn network_disconnect_all() {
+        let server = MockServer::start_sequence(vec![
+            (
+                "200 OK",
+                r#"{"Id":"22be93d5babb","Containers":{"ee1f08ba7d6c":{"Name":"db"},"19a4d5d687db":{"Name":"web"}}}"#,
+            ),
+            ("200 OK", ""),
+        ])
+        .await;
+        let network = server.docker().networks().get("22be93d5babb");
+
+        let ids = network.disconnect_all(true).await.unwrap();
+        assert_eq!(ids, vec!["19a4d5d687db", "ee1f08ba7d6c"]);
+        let disconnect = "/v1.41/networks/22be93d5babb/disconnect".to_string();
+        assert_eq!(
+            server.requests(),
+            vec![
+                (
+                    "GET".to_string(),
+                    "/v1.41/networks/22be93d5babb".to_string()
+                ),
+                ("POST".to_string(), disconnect.clone()),
+                ("POST".to_string(), disconnect),
+            ]
+        );
+
+        let server = MockServer::start("200 OK", r#"{"Id":"22be93d5babb","Containers":{}}"#).await;
+        let network = server.docker().networks().get("22be93d5babb");
+        assert!(network.disconnect_all(false).await.unwrap().is_empty());
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn network_delete_force() {
+        let inspect = r#"{"Id":"22be93d5babb","Containers":{"ee1f08ba7d6c":{"Name":"db"},"19a4d5d687db":{"Name":"web"}}}"#;
+        let disconnect = || {
+            (
+                "POST".to_string(),
+                "/v1.41/networks/22be93d5babb/disconnect".to_string(),
+            )
+        };
+
+        let server = MockServer::start_sequence(vec![
+            ("200 OK", inspect),
+            ("200 OK", ""),
+            ("200 OK", ""),
+            ("204 No Content", ""),
+        ])
+        .await;
+        let network = server.docker().networks().get("22be93d5babb");
+        let ids = network.delete_force().await.unwrap();
+        assert_eq!(ids, vec!["19a4d5d687db", "ee1f08ba7d6c"]);
+        assert_eq!(
+            server.requests(),
+            vec![
+                (
+                    "GET".to_string(),
+                    "/v1.41/networks/22be93d5babb".to_string()
+                ),
+                disconnect(),
+                disconnect(),
+                (
+                    "DELETE".to_string(),
+                    "/v1.41/networks/22be93d5babb".to_string()
+                ),
+            ]
+        );
+
+        let server = MockServer::start_sequence(vec![
+            ("200 OK", inspect),
+            ("200 OK", ""),
+            (
+                "500 Internal Server Error",
+                r#"{"message":"container not found"}"#,
+            ),
+        ])
+        .await;
+        let network = server.docker().networks().get("22be93d5babb");
+        match network.delete_force().await {
+            Err(Error::Fault { code, .. }) => assert_eq!(code, 500),
+            res => panic!("Expected Error::Fault, got {:?}", res),
+        }
+        assert_eq!(
+            server.requests(),
+            vec![
+                (
+                    "GET".to_string(),
+                    "/v1.41/networks/22be93d5babb".to_string()
+                ),
+                disconnect(),
+                disconnect(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn network_list_endpoint() {
+        let server = MockServer::start("200 OK", "[]").await;
+        let networks = server.docker().networks();
+
+        assert!(networks.list(&Default::default()).await.unwrap().is_empty());
+        let opts = NetworkListOpts::builder()
+            .filter([NetworkFilter::Driver("overlay".into())])
+            .build();
+        networks.list(&opts).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0],
+            ("GET".to_string(), "/v1.41/networks".to_string())
+        );
+        let (path, query) = requests[1].1.split_once('?').unwrap();
+        assert_eq!(path, "/v1.41/networks");
+        assert!(!query.is_empty());
+        assert_eq!(query, opts.serialize().unwrap());
+    }
+
+    #[tokio::test]
+    async fn network_prune_endpoint() {
+        let server = MockServer::start("200 OK", r#"{"NetworksDeleted":[]}"#).await;
+        let networks = server.docker().networks();
+        networks.prune(&Default::default()).await.unwrap();
+        assert_eq!(
+            server.requests(),
+            vec![("POST".to_string(), "/v1.41/networks/prune".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn network_create_request() {
+        let server = MockServer::start("201 Created", r#"{"Id":"22be93d5babb"}"#).await;
+        let opts = NetworkCreateOpts::builder("test")
+            .driver("overlay")
+            .attachable(true)
+            .add_label("env", "test")
+            .build()
+            .unwrap();
+
+        server.docker().networks().create(&opts).await.unwrap();
+
+        let requests = server.recorded_requests();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/v1.41/networks/create");
+        assert_eq!(request.header("Content-Type"), Some("application/json"));
+        assert_eq!(
+            request.body_json().unwrap(),
+            serde_json::json!({
+                "Name": "test",
+                "Driver": "overlay",
+                "Attachable": true,
+                "Labels": {"env": "test"}
             })
-    }}
+        );
+    }
+
+    #[tokio::test]
+    async fn network_create_strict() {
+        let server = MockServer::start(
+            "201 Created",
+            r#"{"Id":"22be93d5babb","Warning":"first warning\nsecond warning"}"#,
+        )
+        .await;
+        let docker = server.docker();
+        let opts = NetworkCreateOpts::builder("test").build().unwrap();
+
+        let network = docker.networks().create(&opts).await.unwrap();
+        assert_eq!(network.id().as_ref(), "22be93d5babb");
+
+        match docker.networks().create_strict(&opts).await {
+            Err(Error::Warnings(warnings)) => {
+                assert_eq!(warnings, vec!["first warning", "second warning"]);
+            }
+            Err(e) => panic!("Expected Error::Warnings, got {:?}", e),
+            Ok(_) => panic!("Expected Error::Warnings, the network was created"),
+        }
+    }
 }
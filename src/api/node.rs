@@ -9,7 +9,7 @@ use crate::{
     opts::{NodeListOpts, NodeUpdateOpts},
     Result,
 };
-use containers_api::url::encoded_pair;
+use containers_api::url::{construct_ep, encoded_pair};
 
 impl_api_ty!(Node => name);
 
@@ -23,17 +23,13 @@ impl Node {
 
     api_doc! { Node => Update
     |
-    /// Update a node.
+    /// Update a node, for example to change its availability or role.
     pub async fn update(&self, opts: &NodeUpdateOpts) -> Result<()> {
         self.docker
             .post(
-                &format!(
-                    "/nodes/{}/update?{}",
-                    self.name,
-                    encoded_pair("version", opts.version().to_string())
-                ),
+                &update_ep(&self.name, opts.version()),
                 Payload::Json(opts.serialize()?),
-                Headers::none()
+                Headers::none(),
             )
             .await
             .map(|_| ())
@@ -45,3 +41,21 @@ impl Nodes {
         List -> "/nodes", models::Node
     }
 }
+
+fn update_ep(id: &crate::Id, version: u64) -> String {
+    construct_ep(
+        format!("/nodes/{}/update", id),
+        Some(encoded_pair("version", version)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_update_endpoint() {
+        let id = crate::Id::from("node-1");
+        assert_eq!(update_ep(&id, 12), "/nodes/node-1/update?version=12");
+    }
+}
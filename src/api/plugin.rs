@@ -1,12 +1,13 @@
 //! Install, create and manage plugins
 
 use crate::{
-    conn::{Headers, Payload},
+    conn::{Headers, Payload, AUTH_HEADER},
     models,
-    opts::PluginListOpts,
+    opts::{PluginInstallOpts, PluginListOpts},
     Result,
 };
 use containers_api::url::{construct_ep, encoded_pair};
+use futures_util::{Stream, StreamExt};
 
 use std::path::Path;
 
@@ -76,4 +77,78 @@ impl Plugins {
     impl_api_ep! {plug: Plugin, resp
         List -> "/plugins", models::Plugin
     }
+
+    api_doc! { Plugin => Pull
+    |
+    /// Pull and install a plugin. The returned stream reports the progress of the pull.
+    pub fn install<'docker>(
+        &'docker self,
+        opts: &PluginInstallOpts,
+    ) -> impl Stream<Item = Result<models::ImageBuildChunk>> + Unpin + 'docker {
+        let privileges = match opts.privileges() {
+            Ok(privileges) => privileges,
+            Err(e) => return Box::pin(futures_util::stream::once(async { Err(e) }).left_stream()),
+        };
+
+        let headers = opts.auth_header().map(|a| Headers::single(AUTH_HEADER, a));
+
+        Box::pin(
+            self.docker
+                .post_into_stream(
+                    construct_ep("/plugins/pull", opts.serialize()),
+                    Payload::Json(privileges),
+                    headers,
+                )
+                .right_stream(),
+        )
+    }}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::MockServer;
+
+    #[tokio::test]
+    async fn plugin_enable() {
+        let server = MockServer::start("200 OK", "").await;
+        let docker = server.docker();
+
+        docker
+            .plugins()
+            .get("vieux/sshfs")
+            .enable(Some(30))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            server.requests(),
+            vec![(
+                "POST".to_string(),
+                "/v1.41/plugins/vieux/sshfs/enable?timeout=30".to_string()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn plugin_install() {
+        let server = MockServer::start("200 OK", r#"{"status":"Downloading"}"#).await;
+        let docker = server.docker();
+
+        let opts = PluginInstallOpts::builder("vieux/sshfs:latest").build();
+        let chunks: Vec<_> = docker.plugins().install(&opts).collect().await;
+
+        assert_eq!(chunks.len(), 1);
+        assert!(matches!(
+            &chunks[0],
+            Ok(models::ImageBuildChunk::PullStatus { status, .. }) if status == "Downloading"
+        ));
+        assert_eq!(
+            server.requests(),
+            vec![(
+                "POST".to_string(),
+                "/v1.41/plugins/pull?remote=vieux%2Fsshfs%3Alatest".to_string()
+            )]
+        );
+    }
 }
@@ -4,6 +4,7 @@ use crate::{
     conn::{Headers, Payload},
     models, Result,
 };
+use containers_api::url::{construct_ep, encoded_pair};
 
 impl_api_ty!(Secret => name);
 
@@ -12,7 +13,25 @@ impl Secret {
         Inspect -> &format!("/secrets/{}", secret.name), models::Secret
         Delete -> &format!("/secrets/{}", secret.name), ()
     }
-    // TODO: add Secret::update
+
+    api_doc! { Secret => Update
+    |
+    /// Update this secret. Only the labels of a secret can be updated, other fields of `spec`
+    /// must match the current specification. The `version` must be the current version of the
+    /// secret object as returned by [`Secret::inspect`](Secret::inspect).
+    pub async fn update(&self, version: u64, spec: &models::SecretSpec) -> Result<()> {
+        self.docker
+            .post(
+                &construct_ep(
+                    format!("/secrets/{}/update", self.name),
+                    Some(encoded_pair("version", version)),
+                ),
+                Payload::Json(serde_json::to_string(spec)?),
+                Headers::none(),
+            )
+            .await
+            .map(|_| ())
+    }}
 }
 
 impl Secrets {
@@ -97,7 +116,7 @@ pub mod opts {
         pub fn new<N, D>(name: N, data: D) -> Self
         where
             N: Into<String>,
-            D: AsRef<str>,
+            D: AsRef<[u8]>,
         {
             Self {
                 name: name.into(),
@@ -143,3 +162,23 @@ pub mod opts {
 }
 
 pub use opts::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_create_opts() {
+        let opts = SecretCreateOpts::new("db-password", [0xde_u8, 0xad, 0xbe, 0xef])
+            .add_label("env", "prod");
+
+        let body: serde_json::Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(body["Name"], "db-password");
+        assert_eq!(body["Data"], "3q2+7w==");
+        assert_eq!(body["Labels"], serde_json::json!({"env": "prod"}));
+
+        let opts = SecretCreateOpts::new("token", "secret");
+        let body: serde_json::Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(body["Data"], base64::encode("secret"));
+    }
+}
@@ -5,20 +5,33 @@ use crate::{
     opts::{ServiceListOpts, ServiceOpts},
     Result,
 };
+use containers_api::url::{construct_ep, encoded_pair};
 
 impl_api_ty!(Service => name);
 
 impl Service {
-    api_doc! { Service => Create
-    |
+    #[deprecated(note = "use `Services::create` instead")]
     /// Creates a new service from ServiceOpts.
     pub async fn create(&self, opts: &ServiceOpts) -> Result<models::ServiceCreate201Response> {
+        Services::new(self.docker.clone()).create(opts).await
+    }
+
+    api_doc! { Service => Update
+    |
+    /// Update this service. The `version` must be the current version of the service object as
+    /// returned by [`Service::inspect`](Service::inspect), it is used to avoid conflicting writes.
+    /// The specification in `opts` replaces the current specification of the service.
+    pub async fn update(
+        &self,
+        version: u64,
+        opts: &ServiceOpts,
+    ) -> Result<models::ServiceUpdateResponse> {
         let headers = opts
             .auth_header()
             .map(|a| Headers::single(AUTH_HEADER, a));
         self.docker
             .post_json(
-                "/services/create",
+                &update_ep(&self.name, version),
                 Payload::Json(opts.serialize()?),
                 headers,
             )
@@ -36,4 +49,38 @@ impl Services {
     impl_api_ep! { svc: Service, resp
         List -> "/services", models::Service
     }
+
+    api_doc! { Service => Create
+    |
+    /// Creates a new service from ServiceOpts.
+    pub async fn create(&self, opts: &ServiceOpts) -> Result<models::ServiceCreate201Response> {
+        let headers = opts
+            .auth_header()
+            .map(|a| Headers::single(AUTH_HEADER, a));
+        self.docker
+            .post_json(
+                "/services/create",
+                Payload::Json(opts.serialize()?),
+                headers,
+            )
+            .await
+    }}
+}
+
+fn update_ep(id: &crate::Id, version: u64) -> String {
+    construct_ep(
+        format!("/services/{}/update", id),
+        Some(encoded_pair("version", version)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn service_update_endpoint() {
+        let id = crate::Id::from("web");
+        assert_eq!(update_ep(&id, 7), "/services/web/update?version=7");
+    }
 }
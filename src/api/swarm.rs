@@ -3,9 +3,10 @@
 use crate::{
     conn::{Headers, Payload},
     models,
-    opts::{SwarmInitOpts, SwarmJoinOpts},
+    opts::{SwarmInitOpts, SwarmJoinOpts, SwarmUpdateOpts},
     Docker, Result,
 };
+use containers_api::url::{construct_ep, encoded_pair};
 
 api_doc! { Swarm
 |
@@ -42,10 +43,23 @@ impl Swarm {
 
     api_doc! { Swarm => Init
     |
-    /// Initialize a new swarm.
-    pub async fn initialize(&self, opts: &SwarmInitOpts) -> Result<()> {
+    /// Initialize a new swarm. Returns the ID of the node.
+    pub async fn initialize(&self, opts: &SwarmInitOpts) -> Result<String> {
         self.docker
-            .post("/swarm/init", Payload::Json(opts.serialize()?), Headers::none())
+            .post_json("/swarm/init", Payload::Json(opts.serialize()?), Headers::none())
+            .await
+    }}
+
+    api_doc! { Swarm => Update
+    |
+    /// Update the configuration of the swarm.
+    pub async fn update(&self, opts: &SwarmUpdateOpts) -> Result<()> {
+        self.docker
+            .post(
+                &construct_ep("/swarm/update", Some(opts.query())),
+                Payload::Json(opts.serialize()?),
+                Headers::none(),
+            )
             .await
             .map(|_| ())
     }}
@@ -65,7 +79,7 @@ impl Swarm {
     /// Leave the current swarm.
     pub async fn leave(&self) -> Result<()> {
         self.docker
-            .post("/swarm/leave?force=false", Payload::empty(), Headers::none())
+            .post(&leave_ep(false), Payload::empty(), Headers::none())
             .await
             .map(|_| ())
     }}
@@ -75,8 +89,23 @@ impl Swarm {
     /// Leave the current swarm forcefully, even if this is the last manager or that it will break the cluster.
     pub async fn force_leave(&self) -> Result<()> {
         self.docker
-            .post("/swarm/leave?force=true", Payload::empty(), Headers::none())
+            .post(&leave_ep(true), Payload::empty(), Headers::none())
             .await
             .map(|_| ())
     }}
 }
+
+fn leave_ep(force: bool) -> String {
+    construct_ep("/swarm/leave", Some(encoded_pair("force", force)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swarm_leave_endpoint() {
+        assert_eq!(leave_ep(false), "/swarm/leave?force=false");
+        assert_eq!(leave_ep(true), "/swarm/leave?force=true");
+    }
+}
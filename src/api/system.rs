@@ -1,8 +1,14 @@
-use crate::{models, opts::EventsOpts, Docker, Error, Result};
+use crate::{
+    conn::{Headers, Payload},
+    models,
+    opts::{EventsOpts, RegistryAuth},
+    stream::JsonLineDecoder,
+    Docker, Error, Result,
+};
 use containers_api::url::construct_ep;
-use futures_util::{Stream, TryStreamExt};
+use futures_util::Stream;
 
-use std::{convert::TryFrom, io};
+use std::convert::TryFrom;
 
 impl Docker {
     api_doc! { System => Version
@@ -15,17 +21,45 @@ impl Docker {
     api_doc! { System => Info
     |
     /// Returns system information about Docker instance that is running
+    ///
+    /// Besides the host details this includes the available volume, network, log and
+    /// authorization `plugins`, the `registry_config`, the container `runtimes` with the
+    /// `default_runtime` and the enabled `security_options`. For example the network plugins can
+    /// be checked for the `overlay` driver before creating an overlay network.
     pub async fn info(&self) -> Result<models::SystemInfo> {
         self.get_json("/info").await
     }}
 
+    api_doc! { System => Auth
+    |
+    /// Validate credentials for a registry and, if available, get an identity token for accessing
+    /// the registry without password.
+    pub async fn auth(&self, auth: &RegistryAuth) -> Result<models::SystemAuth200Response> {
+        self.post_json(
+            "/auth",
+            Payload::Json(serde_json::to_string(auth)?),
+            Headers::none(),
+        )
+        .await
+    }}
+
     api_doc! { System => Ping
     |
-    /// This is a dummy endpoint you can use to test if the server is accessible
+    /// This is a dummy endpoint you can use to test if the server is accessible. Fails unless the
+    /// daemon responds with a literal `OK` body.
     pub async fn ping(&self) -> Result<models::PingInfo> {
-        self.get("/_ping")
-            .await
-            .and_then(|resp| models::PingInfo::try_from(resp.headers()))
+        let resp = self.get("/_ping").await?;
+        let info = models::PingInfo::try_from(resp.headers())?;
+
+        let body = hyper::body::to_bytes(resp.into_body()).await?;
+        if body.as_ref() != b"OK" {
+            return Err(Error::InvalidResponse(format!(
+                "expected `OK` ping response, got `{}`",
+                String::from_utf8_lossy(&body)
+            )));
+        }
+
+        Ok(info)
     }}
 
     api_doc! { System => Events
@@ -36,25 +70,74 @@ impl Docker {
         opts: &EventsOpts,
     ) -> impl Stream<Item = Result<models::EventMessage>> + Unpin + 'docker {
         let ep = construct_ep("/events", opts.serialize());
-        let reader = Box::pin(
-            self.get_stream(ep)
-                .map_err(|e| io::Error::new(io::ErrorKind::Other, e)),
-        )
-        .into_async_read();
-
-        Box::pin(
-            asynchronous_codec::FramedRead::new(reader, asynchronous_codec::LinesCodec)
-                .map_err(Error::IO)
-                .and_then(|s: String| async move {
-                    serde_json::from_str(&s).map_err(Error::SerdeJsonError)
-                }),
-        )
+        JsonLineDecoder::new(Box::pin(self.get_stream(ep)))
     }}
 
     api_doc! { System => DataUsage
     |
-    /// Returns data usage of this Docker instance
+    /// Returns data usage of this Docker instance. This includes the size of all layers as well as
+    /// the sizes of images, containers, volumes and the build cache like `docker system df`.
     pub async fn data_usage(&self) -> Result<models::SystemDataUsage200Response> {
         self.get_json("/system/df").await
     }}
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::testutil::MockServer;
+
+    const INFO: &str = r#"{
+        "ID": "7TRN:IPZB:QYBB:VPBQ:UWYS:V4ZK:RIDE:NQUL:D7ZG:OBXE:3QUJ:2VL4",
+        "Plugins": {
+            "Volume": ["local"],
+            "Network": ["bridge", "host", "ipvlan", "macvlan", "null", "overlay"],
+            "Authorization": null,
+            "Log": ["awslogs", "fluentd", "json-file", "local", "syslog"]
+        },
+        "RegistryConfig": {
+            "IndexConfigs": {
+                "docker.io": {"Name": "docker.io", "Mirrors": [], "Secure": true, "Official": true}
+            },
+            "Mirrors": ["https://mirror.example.com/"]
+        },
+        "Runtimes": {
+            "io.containerd.runc.v2": {"path": "runc"},
+            "runc": {"path": "runc"}
+        },
+        "DefaultRuntime": "runc",
+        "SecurityOptions": ["name=apparmor", "name=seccomp,profile=default", "name=cgroupns"]
+    }"#;
+
+    #[tokio::test]
+    async fn info_decode() {
+        let server = MockServer::start("200 OK", INFO).await;
+        let info = server.docker().info().await.unwrap();
+        assert_eq!(
+            server.requests(),
+            vec![("GET".to_string(), "/v1.41/info".to_string())]
+        );
+
+        let plugins = info.plugins.unwrap();
+        assert_eq!(plugins.volume.unwrap(), vec!["local"]);
+        assert!(plugins
+            .network
+            .unwrap()
+            .iter()
+            .any(|driver| driver == "overlay"));
+        assert!(plugins.log.unwrap().contains(&"json-file".to_string()));
+        assert!(plugins.authorization.is_none());
+
+        let registry = info.registry_config.unwrap();
+        assert_eq!(
+            registry.mirrors.unwrap(),
+            vec!["https://mirror.example.com/"]
+        );
+        assert!(registry.index_configs.unwrap().contains_key("docker.io"));
+
+        let runtimes = info.runtimes.unwrap();
+        assert_eq!(runtimes.len(), 2);
+        assert_eq!(runtimes["runc"].path.as_deref(), Some("runc"));
+        assert_eq!(info.default_runtime.as_deref(), Some("runc"));
+        assert_eq!(info.security_options.unwrap().len(), 3);
+    }
+}
@@ -14,7 +14,46 @@ impl Task {
 
 impl Tasks {
     impl_api_ep! { task: Task, resp
-        List -> "/tasks", models::Task
+        List -> "/tasks", models::TaskInfo
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{TaskInfo, TaskState};
+
+    #[test]
+    fn task_info_decode() {
+        let payload = r#"[{
+            "ID": "0kzzo1i0y4jz6027t0k7aezc7",
+            "Version": {"Index": 71},
+            "ServiceID": "9mnpnzenvg8p8tdbtq4wvbkcz",
+            "NodeID": "60gvrl6tm78dmak4yl7srz94v",
+            "Slot": 1,
+            "Status": {
+                "Timestamp": "2016-06-07T21:07:31.290032978Z",
+                "State": "running",
+                "Message": "started",
+                "ContainerStatus": {"ContainerID": "e5d62702a1b4", "PID": 677}
+            },
+            "DesiredState": "running"
+        }]"#;
+
+        let tasks: Vec<TaskInfo> = serde_json::from_str(payload).unwrap();
+        assert_eq!(tasks.len(), 1);
+
+        let task = &tasks[0];
+        assert_eq!(task.id, "0kzzo1i0y4jz6027t0k7aezc7");
+        assert_eq!(
+            task.service_id.as_deref(),
+            Some("9mnpnzenvg8p8tdbtq4wvbkcz")
+        );
+        assert_eq!(task.node_id.as_deref(), Some("60gvrl6tm78dmak4yl7srz94v"));
+        assert_eq!(task.desired_state, Some(TaskState::Running));
+
+        let status = task.status.as_ref().unwrap();
+        assert_eq!(status.state, Some(TaskState::Running));
+        assert_eq!(status.message.as_deref(), Some("started"));
     }
 }
 
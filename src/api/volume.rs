@@ -6,6 +6,7 @@ use crate::{
     opts::{VolumeCreateOpts, VolumeListOpts, VolumePruneOpts},
     Result,
 };
+use containers_api::url::{construct_ep, encoded_pair};
 
 impl_api_ty!(Volume => name);
 
@@ -14,6 +15,17 @@ impl Volume {
         Inspect -> &format!("/volumes/{}", vol.name), models::Volume
         Delete -> &format!("/volumes/{}", vol.name), ()
     }
+
+    api_doc! { Volume => Delete
+    |
+    /// Delete this volume even if it is in use by a container.
+    pub async fn force_delete(&self) -> Result<()> {
+        let ep = construct_ep(
+            format!("/volumes/{}", self.name),
+            Some(encoded_pair("force", true)),
+        );
+        self.docker.delete(&ep).await.map(|_| ())
+    }}
 }
 
 impl Volumes {
@@ -25,7 +37,7 @@ impl Volumes {
     |
     /// List available volumes
     pub async fn list(&self, opts: &VolumeListOpts) -> Result<models::VolumeList200Response> {
-        let ep = containers_api::url::construct_ep("/volumes", opts.serialize());
+        let ep = construct_ep("/volumes", opts.serialize());
         self.docker.get_json(&ep).await
     }}
 
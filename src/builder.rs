@@ -241,7 +241,7 @@ macro_rules! impl_api_ep {
         |
         #[doc = concat!("List available ", stringify!($base), "s.")]
         pub async fn list(&self, opts: &[< $base ListOpts >]) -> Result<Vec<$ret>> {
-            let ep = containers_api::url::construct_ep($ep, opts.serialize());
+            let ep = crate::path::PathBuilder::new($ep).query(opts.serialize()).build();
             self.docker.get_json(&ep).await
         }}
         }
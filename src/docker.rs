@@ -2,11 +2,12 @@
 //!
 //! API Reference: <https://docs.docker.com/engine/api/v1.41/>
 use crate::{
-    conn::{get_http_connector, Headers, Payload, Transport},
+    conn::{self, get_http_connector, Headers, Payload, Transport},
     errors::{Error, Result},
+    models,
+    stream::{Compat, JsonLineDecoder},
     ApiVersion, Containers, Images, Networks, Volumes, LATEST_API_VERSION,
 };
-use containers_api::conn::RequestClient;
 
 #[cfg(feature = "swarm")]
 use crate::{Configs, Nodes, Plugins, Secrets, Services, Swarm, Tasks};
@@ -14,23 +15,41 @@ use crate::{Configs, Nodes, Plugins, Secrets, Services, Swarm, Tasks};
 #[cfg(feature = "tls")]
 use crate::conn::get_https_connector;
 #[cfg(unix)]
-use crate::conn::get_unix_connector;
+use crate::{
+    conn::get_unix_connector,
+    ssh::{SshTunnel, SshUrl},
+};
+#[cfg(unix)]
+use std::sync::Arc;
 
 use futures_util::{
     io::{AsyncRead, AsyncWrite},
     stream::Stream,
+    TryFutureExt, TryStreamExt,
+};
+use hyper::{
+    body::Bytes,
+    header::{self, HeaderMap, HeaderName, HeaderValue},
+    Body, Client, Method, Request, Response, StatusCode,
 };
-use hyper::{body::Bytes, Body, Client, Response};
 use serde::de::DeserializeOwned;
 use std::future::Future;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::time::Duration;
 
 /// Entrypoint interface for communicating with docker daemon
 #[derive(Debug, Clone)]
 pub struct Docker {
     version: ApiVersion,
-    client: RequestClient<Error>,
+    transport: Transport,
+    /// Headers added to every request.
+    headers: HeaderMap,
+    timeout: Option<Duration>,
+    retry: Option<RetryPolicy>,
+    /// Keeps the `ssh` process of clients created from `ssh://` URLs alive.
+    #[cfg(unix)]
+    tunnel: Option<Arc<SshTunnel>>,
 }
 
 impl Docker {
@@ -42,6 +61,9 @@ impl Docker {
     ///  - `tcp://`
     ///  - `http://`
     ///
+    ///  `ssh://` hosts are connected to with the async [Docker::ssh](Docker::ssh) constructor
+    ///  instead, as the tunnel has to be established first.
+    ///
     ///  To create a Docker instance utilizing TLS use explicit [Docker::tls](Docker::tls)
     ///  constructor (this requires `tls` feature enabled).
     ///  
@@ -54,20 +76,54 @@ impl Docker {
         Self::new_versioned(uri, LATEST_API_VERSION)
     }
 
+    /// Returns a [`DockerBuilder`](DockerBuilder) for a docker host at `uri` that allows further
+    /// customization of the client, like setting a request timeout.
+    pub fn builder(uri: impl Into<String>) -> DockerBuilder {
+        DockerBuilder::new(uri)
+    }
+
+    /// Creates a new Docker instance configured from the environment the same way as the docker
+    /// CLI:
+    ///  - `DOCKER_HOST` is the URI of the docker host, see [`Docker::new`](Docker::new) for
+    ///    supported schemes. Defaults to `unix:///var/run/docker.sock`. `ssh://` hosts are
+    ///    rejected with an error, use [`Docker::from_env_async`](Docker::from_env_async) for them.
+    ///  - `DOCKER_TLS_VERIFY` enables TLS with verification of the host certificate for `tcp://`
+    ///    hosts when set to a non-empty value. This requires the `tls` feature.
+    ///  - `DOCKER_CERT_PATH` is the directory containing `ca.pem`, `cert.pem` and `key.pem` used
+    ///    when TLS is enabled. Defaults to `~/.docker`.
+    ///  - `DOCKER_API_VERSION` is the API version to use. Defaults to
+    ///    [`LATEST_API_VERSION`](crate::LATEST_API_VERSION).
+    ///
+    /// Returns an error if any of the variables is invalid or reading the certificate files
+    /// fails.
+    pub fn from_env() -> Result<Docker> {
+        EnvConfig::from_lookup(|name| std::env::var(name).ok())?.connect()
+    }
+
+    /// Same as [`Docker::from_env`](Docker::from_env) but `ssh://` hosts in `DOCKER_HOST` are
+    /// supported as well and connected to with [`Docker::ssh`](Docker::ssh).
+    pub async fn from_env_async() -> Result<Docker> {
+        EnvConfig::from_lookup(|name| std::env::var(name).ok())?
+            .connect_async()
+            .await
+    }
+
     /// Same as [`Docker::new`](Docker::new) but the API version can be explicitly specified.
     pub fn new_versioned<U>(uri: U, version: impl Into<ApiVersion>) -> Result<Docker>
     where
         U: AsRef<str>,
     {
-        let version = version.into();
-        let uri = uri.as_ref();
+        Self::new_with_pool(uri.as_ref(), version.into(), PoolConfig::default())
+    }
+
+    fn new_with_pool(uri: &str, version: ApiVersion, pool: PoolConfig) -> Result<Docker> {
         let mut it = uri.split("://");
 
         match it.next() {
             #[cfg(unix)]
             Some("unix") => {
-                if let Some(path) = it.next() {
-                    Ok(Docker::unix_versioned(path, version))
+                if let Some(path) = it.next().filter(|path| !path.is_empty()) {
+                    Ok(Docker::unix_with_pool(path, version, pool))
                 } else {
                     Err(Error::MissingAuthority)
                 }
@@ -75,12 +131,18 @@ impl Docker {
             #[cfg(not(unix))]
             Some("unix") => Err(Error::UnsupportedScheme("unix".to_string())),
             Some("tcp") | Some("http") => {
-                if let Some(host) = it.next() {
-                    Docker::tcp_versioned(host, version)
+                if let Some(host) = it.next().filter(|host| !host.is_empty()) {
+                    Docker::tcp_with_pool(host, version, pool)
                 } else {
                     Err(Error::MissingAuthority)
                 }
             }
+            #[cfg(unix)]
+            Some("ssh") => Err(Error::StringError(
+                "`ssh://` hosts have to be connected to with `Docker::ssh`".to_string(),
+            )),
+            #[cfg(not(unix))]
+            Some("ssh") => Err(Error::UnsupportedScheme("ssh".to_string())),
             Some(scheme) => Err(Error::UnsupportedScheme(scheme.to_string())),
             None => unreachable!(), // This is never possible because calling split on an empty string
                                     // always returns at least one element
@@ -110,20 +172,69 @@ impl Docker {
     where
         P: AsRef<Path>,
     {
+        Self::unix_with_pool(socket_path, version.into(), PoolConfig::default())
+    }
+
+    #[cfg(unix)]
+    fn unix_with_pool<P>(socket_path: P, version: ApiVersion, pool: PoolConfig) -> Docker
+    where
+        P: AsRef<Path>,
+    {
+        // Idle connections to the socket are not kept open by default
+        let pool = PoolConfig {
+            max_idle_per_host: pool.max_idle_per_host.or(Some(0)),
+            ..pool
+        };
         Docker {
-            version: version.into(),
-            client: RequestClient::new(
-                Transport::Unix {
-                    client: Client::builder()
-                        .pool_max_idle_per_host(0)
-                        .build(get_unix_connector()),
-                    path: socket_path.as_ref().to_path_buf(),
-                },
-                Box::new(validate_response),
-            ),
+            version,
+            transport: Transport::Unix {
+                client: pool.client_builder().build(get_unix_connector()),
+                path: socket_path.as_ref().to_path_buf(),
+            },
+            headers: HeaderMap::new(),
+            timeout: None,
+            retry: None,
+            #[cfg(unix)]
+            tunnel: None,
         }
     }
 
+    #[cfg(unix)]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    /// Creates a new docker instance for a docker host reachable over SSH at a URI like
+    /// `ssh://[user@]host[:port][/socket]`.
+    ///
+    /// The remote docker socket, `/var/run/docker.sock` unless a path is given, is forwarded to
+    /// a local Unix socket by running `ssh -L`. The `ssh` binary has to be in `PATH` and
+    /// authentication must not require any user input, for example by using an SSH agent.
+    /// Returns an error if the tunnel isn't established within 30 seconds. The `ssh` process is
+    /// stopped once the last clone of the returned instance is dropped.
+    ///
+    ///  Uses [`LATEST_API_VERSION`](crate::LATEST_API_VERSION), to use a specific version see
+    ///  [`Docker::ssh_versioned`](Docker::ssh_versioned).
+    pub async fn ssh<U>(uri: U) -> Result<Docker>
+    where
+        U: AsRef<str>,
+    {
+        Self::ssh_versioned(uri, LATEST_API_VERSION).await
+    }
+
+    #[cfg(unix)]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    /// Same as [`Docker::ssh`](Docker::ssh) but the API version can be explicitly specified.
+    pub async fn ssh_versioned<U>(uri: U, version: impl Into<ApiVersion>) -> Result<Docker>
+    where
+        U: AsRef<str>,
+    {
+        let url = SshUrl::parse(uri.as_ref())?;
+        let version = version.into();
+        let tunnel = SshTunnel::open(&url).await?;
+        let mut docker =
+            Docker::unix_with_pool(tunnel.local_socket(), version, PoolConfig::default());
+        docker.tunnel = Some(Arc::new(tunnel));
+        Ok(docker)
+    }
+
     #[cfg(feature = "tls")]
     #[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
     /// Creates a new docker instance for a docker host listening on a given TCP socket `host`.
@@ -162,15 +273,16 @@ impl Docker {
     {
         Ok(Docker {
             version: version.into(),
-            client: RequestClient::new(
-                Transport::EncryptedTcp {
-                    client: Client::builder()
-                        .build(get_https_connector(cert_path.as_ref(), verify)?),
-                    host: url::Url::parse(&format!("https://{}", host.as_ref()))
-                        .map_err(Error::InvalidUrl)?,
-                },
-                Box::new(validate_response),
-            ),
+            transport: Transport::EncryptedTcp {
+                client: Client::builder().build(get_https_connector(cert_path.as_ref(), verify)?),
+                host: url::Url::parse(&format!("https://{}", host.as_ref()))
+                    .map_err(Error::InvalidUrl)?,
+            },
+            headers: HeaderMap::new(),
+            timeout: None,
+            retry: None,
+            #[cfg(unix)]
+            tunnel: None,
         })
     }
 
@@ -193,19 +305,28 @@ impl Docker {
 
     /// Same as [`Docker::tcp`](Docker::tcp) but the API version can be explicitly specified.
     pub fn tcp_versioned<H>(host: H, version: impl Into<ApiVersion>) -> Result<Docker>
+    where
+        H: AsRef<str>,
+    {
+        Self::tcp_with_pool(host, version.into(), PoolConfig::default())
+    }
+
+    fn tcp_with_pool<H>(host: H, version: ApiVersion, pool: PoolConfig) -> Result<Docker>
     where
         H: AsRef<str>,
     {
         Ok(Docker {
-            version: version.into(),
-            client: RequestClient::new(
-                Transport::Tcp {
-                    client: Client::builder().build(get_http_connector()),
-                    host: url::Url::parse(&format!("tcp://{}", host.as_ref()))
-                        .map_err(Error::InvalidUrl)?,
-                },
-                Box::new(validate_response),
-            ),
+            version,
+            transport: Transport::Tcp {
+                client: pool.client_builder().build(get_http_connector()),
+                host: url::Url::parse(&format!("tcp://{}", host.as_ref()))
+                    .map_err(Error::InvalidUrl)?,
+            },
+            headers: HeaderMap::new(),
+            timeout: None,
+            retry: None,
+            #[cfg(unix)]
+            tunnel: None,
         })
     }
 
@@ -229,15 +350,56 @@ impl Docker {
         Volumes::new(self.clone())
     }
 
-    /// Verifies the API version returned by the server and adjusts the version used by this client
-    /// in future requests.
-    pub async fn adjust_api_version(&mut self) -> Result<()> {
-        let server_version: ApiVersion = self.version().await.and_then(|v| {
-            v.api_version
-                .unwrap_or_default()
-                .parse::<ApiVersion>()
-                .map_err(Error::MalformedVersion)
-        })?;
+    /// Returns a copy of this client using a different request timeout, `None` disables the
+    /// timeout. Useful to fail fast on a single call or to give a slow call more time than the
+    /// timeout configured with [`DockerBuilder::request_timeout`](DockerBuilder::request_timeout).
+    /// When exceeded the request fails with [`Error::Timeout`](crate::Error::Timeout).
+    ///
+    /// Returns [`Error::InvalidTimeout`](crate::Error::InvalidTimeout) if the timeout is zero.
+    ///
+    /// ```no_run
+    /// # async fn example(docker: docker_api::Docker) -> docker_api::Result<()> {
+    /// use std::time::Duration;
+    ///
+    /// let info = docker
+    ///     .with_request_timeout(Some(Duration::from_secs(1)))?
+    ///     .containers()
+    ///     .get("web")
+    ///     .inspect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_request_timeout(&self, timeout: Option<Duration>) -> Result<Docker> {
+        if let Some(timeout) = timeout.filter(Duration::is_zero) {
+            return Err(Error::InvalidTimeout(timeout));
+        }
+
+        Ok(Docker {
+            timeout,
+            ..self.clone()
+        })
+    }
+
+    /// Negotiates the API version with the server. All further requests of this instance use
+    /// the lower of the version supported by the server and the version of this client.
+    pub async fn negotiate_version(&mut self) -> Result<()> {
+        // Use the unversioned endpoint as the server might not support the current version.
+        let server_version: ApiVersion = self
+            .with_retry(|| {
+                self.send_json::<models::SystemVersion, _>(
+                    Method::GET,
+                    "/version",
+                    Payload::empty(),
+                )
+            })
+            .await
+            .and_then(|v| {
+                v.api_version
+                    .unwrap_or_default()
+                    .parse::<ApiVersion>()
+                    .map_err(Error::MalformedVersion)
+            })?;
 
         if server_version <= self.version {
             self.version = server_version;
@@ -246,23 +408,219 @@ impl Docker {
         Ok(())
     }
 
+    #[deprecated(note = "use `Docker::negotiate_version` instead")]
+    /// Verifies the API version returned by the server and adjusts the version used by this client
+    /// in future requests.
+    pub async fn adjust_api_version(&mut self) -> Result<()> {
+        self.negotiate_version().await
+    }
+
+    //####################################################################################################
+    //
+    // Raw requests
+    //
+    //####################################################################################################
+
+    /// Sends a request to an arbitrary `endpoint` and returns the unprocessed response.
+    ///
+    /// This is an escape hatch for endpoints that are not wrapped by this crate, prefer the typed
+    /// interfaces where available. The endpoint is prefixed with the API version of this client,
+    /// so `/_ping` is sent as `/v1.41/_ping`. Responses with an error status are returned as
+    /// [`Error::Fault`](crate::Error::Fault). Only `GET`, `HEAD`, `POST`, `PUT` and `DELETE`
+    /// requests are supported. The body is a [`Payload`](crate::conn::Payload).
+    ///
+    /// ```no_run
+    /// # async fn run() -> docker_api::Result<()> {
+    /// use docker_api::{conn::Payload, Docker};
+    /// use hyper::Method;
+    ///
+    /// let docker = Docker::new("unix:///var/run/docker.sock")?;
+    /// let response = docker
+    ///     .request(Method::GET, "/_ping", Payload::empty())
+    ///     .await?;
+    /// println!("{}", response.status());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn request<B>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: Payload<B>,
+    ) -> Result<Response<Body>>
+    where
+        B: Into<Body>,
+    {
+        match method {
+            Method::GET => self.get(endpoint).await,
+            Method::HEAD => self.head(endpoint).await,
+            Method::POST => self.post(endpoint, body, Headers::none()).await,
+            method @ (Method::PUT | Method::DELETE) => {
+                let ep = self.version.make_endpoint(endpoint);
+                self.with_timeout(self.send(method, &ep, body, Headers::none()))
+                    .await
+            }
+            method => Err(Error::StringError(format!(
+                "unsupported request method `{method}`"
+            ))),
+        }
+    }
+
+    /// Sends a `GET` request to an arbitrary `endpoint` and returns the raw response body. See
+    /// [`Docker::request`](Docker::request) for details.
+    pub async fn get_raw(&self, endpoint: &str) -> Result<Bytes> {
+        let response = self.get(endpoint).await?;
+        hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Sends a `POST` request with `body` to an arbitrary `endpoint` and returns the raw response
+    /// body. See [`Docker::request`](Docker::request) for details.
+    ///
+    /// ```no_run
+    /// # async fn run() -> docker_api::Result<()> {
+    /// use docker_api::{conn::Payload, Docker};
+    ///
+    /// let docker = Docker::new("unix:///var/run/docker.sock")?;
+    /// let body = docker
+    ///     .post_raw("/containers/create", Payload::Json(r#"{"Image":"alpine"}"#))
+    ///     .await?;
+    /// println!("{}", String::from_utf8_lossy(&body));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn post_raw<B>(&self, endpoint: &str, body: Payload<B>) -> Result<Bytes>
+    where
+        B: Into<Body>,
+    {
+        let response = self.post(endpoint, body, Headers::none()).await?;
+        hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(Error::from)
+    }
+
     //####################################################################################################
     //
     // Utility functions to make requests
     //
     //####################################################################################################
 
+    /// Applies the configured request timeout to `request`.
+    async fn with_timeout<T>(&self, request: impl Future<Output = Result<T>>) -> Result<T> {
+        match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, request)
+                .await
+                .map_err(|_| Error::Timeout)?,
+            None => request.await,
+        }
+    }
+
+    /// Sends an idempotent request created by `request`, retrying it with exponential backoff
+    /// according to the configured retry policy if it fails with a transport error.
+    async fn with_retry<T, F, Fut>(&self, request: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let policy = match self.retry {
+            Some(policy) => policy,
+            None => return self.with_timeout(request()).await,
+        };
+
+        let mut attempt = 1;
+        loop {
+            match self.with_timeout(request()).await {
+                Err(e) if attempt < policy.max_attempts && is_transient(&e) => {
+                    let delay = policy
+                        .base_delay
+                        .saturating_mul(2u32.saturating_pow(attempt - 1));
+                    log::debug!("request attempt {attempt} failed, retrying in {delay:?} - {e}");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Sends a request to `ep`, an endpoint already prefixed with the API version, with the
+    /// default headers of this client followed by `headers`. Responses with an error status are
+    /// returned as errors.
+    async fn send<B>(
+        &self,
+        method: Method,
+        ep: &str,
+        body: Payload<B>,
+        headers: Option<Headers>,
+    ) -> Result<Response<Body>>
+    where
+        B: Into<Body>,
+    {
+        let content_type = body.mime_type();
+        let mut request = Request::builder()
+            .method(method)
+            .uri(self.transport.make_uri(ep)?)
+            .header(header::HOST, "")
+            .body(
+                body.into_inner()
+                    .map(Into::into)
+                    .unwrap_or_else(Body::empty),
+            )
+            .map_err(conn::Error::from)?;
+
+        let request_headers = request.headers_mut();
+        request_headers.extend(self.headers.clone());
+        for (name, value) in headers.into_iter().flatten() {
+            request_headers.insert(name, header_value(&value)?);
+        }
+        if let Some(mime) = content_type {
+            request_headers.insert(header::CONTENT_TYPE, header_value(mime.as_ref())?);
+        }
+
+        let response = self.transport.request(request).await?;
+        validate_response(response).await
+    }
+
+    async fn send_string<B>(
+        &self,
+        method: Method,
+        ep: &str,
+        body: Payload<B>,
+        headers: Option<Headers>,
+    ) -> Result<String>
+    where
+        B: Into<Body>,
+    {
+        let response = self.send(method, ep, body, headers).await?;
+        let body = hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(conn::Error::from)?;
+        String::from_utf8(body.to_vec()).map_err(|e| conn::Error::from(e).into())
+    }
+
+    async fn send_json<T, B>(&self, method: Method, ep: &str, body: Payload<B>) -> Result<T>
+    where
+        T: DeserializeOwned,
+        B: Into<Body>,
+    {
+        let raw = self.send_string(method, ep, body, Headers::none()).await?;
+        log::trace!("{raw}");
+        serde_json::from_str(&raw).map_err(Error::from)
+    }
+
     pub(crate) async fn get(&self, endpoint: &str) -> Result<Response<Body>> {
-        self.client.get(self.version.make_endpoint(endpoint)).await
+        let ep = self.version.make_endpoint(endpoint);
+        self.with_retry(|| self.send(Method::GET, &ep, Payload::empty(), Headers::none()))
+            .await
     }
 
     pub(crate) async fn get_json<T: DeserializeOwned>(&self, endpoint: &str) -> Result<T> {
-        self.client
-            .get_json(self.version.make_endpoint(endpoint))
+        let ep = self.version.make_endpoint(endpoint);
+        self.with_retry(|| self.send_json(Method::GET, &ep, Payload::empty()))
             .await
     }
 
-    #[allow(dead_code)]
     pub(crate) async fn post<B>(
         &self,
         endpoint: &str,
@@ -272,8 +630,8 @@ impl Docker {
     where
         B: Into<Body>,
     {
-        self.client
-            .post(self.version.make_endpoint(endpoint), body, headers)
+        let ep = self.version.make_endpoint(endpoint);
+        self.with_timeout(self.send(Method::POST, &ep, body, headers))
             .await
     }
 
@@ -286,8 +644,8 @@ impl Docker {
     where
         B: Into<Body>,
     {
-        self.client
-            .post_string(self.version.make_endpoint(endpoint), body, headers)
+        let ep = self.version.make_endpoint(endpoint);
+        self.with_timeout(self.send_string(Method::POST, &ep, body, headers))
             .await
     }
 
@@ -301,34 +659,40 @@ impl Docker {
         T: DeserializeOwned,
         B: Into<Body>,
     {
-        self.client
-            .post_json(self.version.make_endpoint(endpoint), body, headers)
-            .await
+        let ep = self.version.make_endpoint(endpoint);
+        let request = async {
+            let raw = self.send_string(Method::POST, &ep, body, headers).await?;
+            log::trace!("{raw}");
+            serde_json::from_str(&raw).map_err(Error::from)
+        };
+        self.with_timeout(request).await
     }
 
     pub(crate) async fn put<B>(&self, endpoint: &str, body: Payload<B>) -> Result<String>
     where
         B: Into<Body>,
     {
-        self.client
-            .put_string(self.version.make_endpoint(endpoint), body)
+        let ep = self.version.make_endpoint(endpoint);
+        self.with_timeout(self.send_string(Method::PUT, &ep, body, Headers::none()))
             .await
     }
 
     pub(crate) async fn delete(&self, endpoint: &str) -> Result<String> {
-        self.client
-            .delete_string(self.version.make_endpoint(endpoint))
+        let ep = self.version.make_endpoint(endpoint);
+        self.with_timeout(self.send_string(Method::DELETE, &ep, Payload::empty(), Headers::none()))
             .await
     }
 
     pub(crate) async fn delete_json<T: DeserializeOwned>(&self, endpoint: &str) -> Result<T> {
-        self.client
-            .delete_json(self.version.make_endpoint(endpoint))
+        let ep = self.version.make_endpoint(endpoint);
+        self.with_timeout(self.send_json(Method::DELETE, &ep, Payload::empty()))
             .await
     }
 
     pub(crate) async fn head(&self, endpoint: &str) -> Result<Response<Body>> {
-        self.client.head(self.version.make_endpoint(endpoint)).await
+        let ep = self.version.make_endpoint(endpoint);
+        self.with_retry(|| self.send(Method::HEAD, &ep, Payload::empty(), Headers::none()))
+            .await
     }
 
     /// Send a streaming post request.
@@ -343,11 +707,13 @@ impl Docker {
     where
         B: Into<Body> + 'a,
     {
-        self.client
-            .post_stream(self.version.make_endpoint(endpoint), body, headers)
+        let ep = self.version.make_endpoint(endpoint);
+        async move { self.send(Method::POST, &ep, body, headers).await }
+            .map_ok(body_stream)
+            .try_flatten_stream()
     }
 
-    /// Send a streaming post request that returns a stream of JSON values
+    /// Send a streaming post request that returns a stream of newline delimited JSON values
     ///
     /// When a received chunk does not contain a full JSON reads more chunks from the stream
     pub(crate) fn post_into_stream<'a, B, T>(
@@ -360,17 +726,24 @@ impl Docker {
         B: Into<Body> + 'a,
         T: DeserializeOwned + 'a,
     {
-        self.client
-            .post_into_stream(self.version.make_endpoint(endpoint), body, headers)
+        JsonLineDecoder::new(Box::pin(self.post_stream(endpoint, body, headers)))
     }
 
     pub(crate) fn get_stream<'a>(
         &'a self,
         endpoint: impl AsRef<str> + Unpin + 'a,
     ) -> impl Stream<Item = Result<Bytes>> + 'a {
-        self.client.get_stream(self.version.make_endpoint(endpoint))
+        let ep = self.version.make_endpoint(endpoint);
+        async move {
+            self.send(Method::GET, &ep, Payload::empty(), Headers::none())
+                .await
+        }
+        .map_ok(body_stream)
+        .try_flatten_stream()
     }
 
+    /// Sends a post request upgrading the connection to a raw stream, used to attach to
+    /// containers and execs.
     pub(crate) async fn post_upgrade_stream<'a, B>(
         &'a self,
         endpoint: impl AsRef<str> + 'a,
@@ -379,12 +752,233 @@ impl Docker {
     where
         B: Into<Body> + 'a,
     {
-        self.client
-            .post_upgrade_stream(self.version.make_endpoint(endpoint), body)
+        let mut headers = Headers::default();
+        headers.add("Connection", "Upgrade");
+        headers.add("Upgrade", "tcp");
+
+        let ep = self.version.make_endpoint(endpoint);
+        let response = self.send(Method::POST, &ep, body, Some(headers)).await?;
+        if response.status() != StatusCode::SWITCHING_PROTOCOLS {
+            return Err(conn::Error::ConnectionNotUpgraded.into());
+        }
+        let upgraded = hyper::upgrade::on(response)
             .await
+            .map_err(conn::Error::from)?;
+        Ok(Compat::new(upgraded))
+    }
+}
+
+/// Builder interface for [`Docker`](Docker) allowing further customization of the client.
+#[derive(Debug, Clone)]
+pub struct DockerBuilder {
+    uri: String,
+    version: ApiVersion,
+    timeout: Option<Duration>,
+    retry: Option<RetryPolicy>,
+    pool: PoolConfig,
+    headers: Vec<(String, String)>,
+}
+
+impl DockerBuilder {
+    /// Creates a new builder for a docker host at `uri`. See [`Docker::new`](Docker::new) for
+    /// supported schemes.
+    pub fn new(uri: impl Into<String>) -> Self {
+        DockerBuilder {
+            uri: uri.into(),
+            version: LATEST_API_VERSION,
+            timeout: None,
+            retry: None,
+            pool: PoolConfig::default(),
+            headers: Vec::new(),
+        }
+    }
+
+    /// The API version used as prefix of all request paths, useful to pin the version when
+    /// talking to older daemons. Defaults to [`LATEST_API_VERSION`](crate::LATEST_API_VERSION).
+    pub fn api_version(mut self, version: impl Into<ApiVersion>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// Maximum duration of a single request. When exceeded the request fails with
+    /// [`Error::Timeout`](crate::Error::Timeout). Streaming endpoints like logs or events are not
+    /// affected by this timeout.
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retry idempotent `GET` and `HEAD` requests that fail because of a transport error, like a
+    /// connection reset, up to `max_attempts` times in total. The delay before each retry starts
+    /// at `base_delay` and doubles with every attempt. Other requests are never retried.
+    pub fn retry(mut self, max_attempts: u32, base_delay: Duration) -> Self {
+        self.retry = Some(RetryPolicy {
+            max_attempts,
+            base_delay,
+        });
+        self
+    }
+
+    /// Maximum number of idle connections kept open to the docker host. Setting it to `0`
+    /// disables connection reuse. Connections over a Unix socket are not kept open by default,
+    /// for TCP connections there is no limit.
+    pub fn pool_max_idle_per_host(mut self, max_idle: usize) -> Self {
+        self.pool.max_idle_per_host = Some(max_idle);
+        self
+    }
+
+    /// Duration after which idle connections to the docker host are closed.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Add a header sent with every request, for example to authenticate against a proxy in
+    /// front of the docker host. Setting the same header again replaces the previous value,
+    /// headers set by individual requests take precedence.
+    pub fn default_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Creates the final [`Docker`](Docker) instance. Returns an error if the `uri` or a default
+    /// header is invalid or the request timeout is zero.
+    pub fn build(self) -> Result<Docker> {
+        if let Some(timeout) = self.timeout.filter(Duration::is_zero) {
+            return Err(Error::InvalidTimeout(timeout));
+        }
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| conn::Error::from(hyper::http::Error::from(e)))?;
+            headers.insert(name, header_value(value)?);
+        }
+
+        let mut docker = Docker::new_with_pool(&self.uri, self.version, self.pool)?;
+        docker.timeout = self.timeout;
+        docker.retry = self.retry;
+        docker.headers = headers;
+        Ok(docker)
     }
 }
 
+/// Connection settings of the docker CLI environment variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EnvConfig {
+    host: String,
+    version: ApiVersion,
+    /// Directory of the client certificates if TLS verification is enabled.
+    tls_cert_path: Option<PathBuf>,
+}
+
+impl EnvConfig {
+    const DEFAULT_HOST: &'static str = "unix:///var/run/docker.sock";
+
+    /// Reads the configuration from variables returned by `lookup`, empty values are treated as
+    /// unset.
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<EnvConfig> {
+        let var = |name| lookup(name).filter(|value| !value.is_empty());
+
+        let version = match var("DOCKER_API_VERSION") {
+            Some(version) => version.parse()?,
+            None => LATEST_API_VERSION,
+        };
+        let tls_cert_path = var("DOCKER_TLS_VERIFY").map(|_| {
+            var("DOCKER_CERT_PATH")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| {
+                    var("HOME")
+                        .map(PathBuf::from)
+                        .unwrap_or_default()
+                        .join(".docker")
+                })
+        });
+
+        Ok(EnvConfig {
+            host: var("DOCKER_HOST").unwrap_or_else(|| Self::DEFAULT_HOST.to_owned()),
+            version,
+            tls_cert_path,
+        })
+    }
+
+    /// Returns the authority of the host if the connection uses TLS. Like with the docker CLI
+    /// TLS settings are ignored for hosts not connected over TCP.
+    fn tls_host(&self) -> Option<&str> {
+        self.tls_cert_path.as_ref()?;
+        match self.host.split_once("://")? {
+            ("tcp" | "https", host) if !host.is_empty() => Some(host),
+            _ => None,
+        }
+    }
+
+    fn connect(self) -> Result<Docker> {
+        match (self.tls_host(), &self.tls_cert_path) {
+            #[cfg(feature = "tls")]
+            (Some(host), Some(cert_path)) => {
+                Docker::tls_versioned(host, self.version, cert_path, true)
+            }
+            #[cfg(not(feature = "tls"))]
+            (Some(_), Some(_)) => Err(Error::StringError(
+                "`DOCKER_TLS_VERIFY` is set but TLS requires the `tls` feature".into(),
+            )),
+            _ => Docker::new_with_pool(&self.host, self.version, PoolConfig::default()),
+        }
+    }
+
+    /// Same as `connect` but also connects to `ssh://` hosts.
+    async fn connect_async(self) -> Result<Docker> {
+        #[cfg(unix)]
+        if self.host.starts_with("ssh://") {
+            return Docker::ssh_versioned(&self.host, self.version).await;
+        }
+        self.connect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct PoolConfig {
+    max_idle_per_host: Option<usize>,
+    idle_timeout: Option<Duration>,
+}
+
+impl PoolConfig {
+    fn client_builder(&self) -> hyper::client::Builder {
+        let mut builder = Client::builder();
+        if let Some(max_idle) = self.max_idle_per_host {
+            builder.pool_max_idle_per_host(max_idle);
+        }
+        if let Some(timeout) = self.idle_timeout {
+            builder.pool_idle_timeout(timeout);
+        }
+        builder
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+}
+
+/// Parses a default header value.
+fn header_value(value: &str) -> Result<HeaderValue> {
+    HeaderValue::from_str(value).map_err(|e| conn::Error::from(hyper::http::Error::from(e)).into())
+}
+
+/// Returns the chunks of the body of `response`.
+fn body_stream(response: Response<Body>) -> impl Stream<Item = Result<Bytes>> {
+    response
+        .into_body()
+        .map_err(|e| conn::Error::from(e).into())
+}
+
+/// Returns `true` if the request failed before a response was received, for example because the
+/// connection was reset.
+pub(crate) fn is_transient(e: &Error) -> bool {
+    matches!(e, Error::Error(_) | Error::Hyper(_) | Error::IO(_))
+}
+
 fn validate_response(
     response: Response<Body>,
 ) -> Pin<Box<dyn Future<Output = Result<Response<Body>>> + Send + Sync>> {
@@ -409,6 +1003,9 @@ fn validate_response(
             | StatusCode::CREATED
             | StatusCode::SWITCHING_PROTOCOLS
             | StatusCode::NO_CONTENT => Ok(response),
+            // Returned by container lifecycle endpoints if the container is already in the
+            // requested state, for example when starting a running container.
+            StatusCode::NOT_MODIFIED => Err(Error::NotModified),
             // Error case: try to deserialize error message
             _ => {
                 let body = response.into_body();
@@ -417,13 +1014,20 @@ fn validate_response(
                     .map_err(conn::Error::from)?;
                 let message_body = String::from_utf8(bytes.to_vec()).map_err(conn::Error::from)?;
                 log::trace!("{message_body:#?}");
+                // Fall back to the raw body if it isn't JSON or to the status description if the
+                // body is empty.
                 let message = serde_json::from_str::<ErrorResponse>(&message_body)
                     .map(|e| e.message)
                     .unwrap_or_else(|_| {
-                        status
-                            .canonical_reason()
-                            .unwrap_or("unknown error code")
-                            .to_owned()
+                        let raw = message_body.trim();
+                        if raw.is_empty() {
+                            status
+                                .canonical_reason()
+                                .unwrap_or("unknown error code")
+                                .to_owned()
+                        } else {
+                            raw.to_owned()
+                        }
                     });
                 Err(Error::Fault {
                     code: status,
@@ -474,7 +1078,20 @@ impl Docker {
 
 #[cfg(test)]
 mod tests {
-    use super::{Docker, Error};
+    use super::{validate_response, Docker, EnvConfig, Error, Method};
+    use crate::{conn::Payload, testutil::MockServer, ApiVersion};
+    use std::time::Duration;
+
+    const OK: &str = "HTTP/1.1 200 OK\r\nconnection: close\r\ncontent-length: 2\r\n\r\nOK";
+
+    fn paths(server: &MockServer) -> Vec<String> {
+        server
+            .requests()
+            .into_iter()
+            .map(|(_, path)| path)
+            .collect()
+    }
+
     #[test]
     fn creates_correct_docker() {
         let d = Docker::new("tcp://127.0.0.1:80");
@@ -517,4 +1134,398 @@ mod tests {
             e => panic!(r#"Expected Error::UnsupportedScheme(""), got {}"#, e),
         }
     }
+
+    #[tokio::test]
+    async fn maps_not_modified_response() {
+        let response = hyper::Response::builder()
+            .status(304)
+            .body(hyper::Body::empty())
+            .unwrap();
+        match validate_response(response).await {
+            Err(Error::NotModified) => {}
+            other => panic!("Expected Error::NotModified, got {:?}", other.map(|_| ())),
+        }
+
+        let response = hyper::Response::builder()
+            .status(404)
+            .body(hyper::Body::from(
+                r#"{"message": "No such container: test"}"#,
+            ))
+            .unwrap();
+        match validate_response(response).await {
+            Err(Error::Fault { code, message }) => {
+                assert_eq!(code, 404);
+                assert_eq!(message, "No such container: test");
+            }
+            other => panic!("Expected Error::Fault, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[tokio::test]
+    async fn maps_non_json_error_response() {
+        let response = hyper::Response::builder()
+            .status(500)
+            .body(hyper::Body::from("page not found\n"))
+            .unwrap();
+        match validate_response(response).await {
+            Err(Error::Fault { code, message }) => {
+                assert_eq!(code, 500);
+                assert_eq!(message, "page not found");
+            }
+            other => panic!("Expected Error::Fault, got {:?}", other.map(|_| ())),
+        }
+
+        let response = hyper::Response::builder()
+            .status(404)
+            .body(hyper::Body::empty())
+            .unwrap();
+        match validate_response(response).await {
+            Err(Error::Fault { code, message }) => {
+                assert_eq!(code, 404);
+                assert_eq!(message, "Not Found");
+            }
+            other => panic!("Expected Error::Fault, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[tokio::test]
+    async fn global_request_timeout() {
+        let server = MockServer::start_unresponsive().await;
+        let docker = Docker::builder(server.uri())
+            .request_timeout(Duration::from_millis(50))
+            .build()
+            .unwrap();
+
+        let result = docker.get_json::<serde_json::Value>("/info").await;
+        assert!(matches!(result, Err(Error::Timeout)));
+
+        let result = docker
+            .post_string("/containers/test/start", Payload::empty(), None)
+            .await;
+        assert!(matches!(result, Err(Error::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn per_request_timeout() {
+        let server = MockServer::start_unresponsive().await;
+        let docker = server.docker();
+
+        let result = docker
+            .with_request_timeout(Some(Duration::from_millis(50)))
+            .unwrap()
+            .get_raw("/_ping")
+            .await;
+        assert!(matches!(result, Err(Error::Timeout)));
+        assert_eq!(docker.timeout, None);
+
+        let docker = Docker::builder(server.uri())
+            .request_timeout(Duration::from_secs(5))
+            .build()
+            .unwrap();
+        assert_eq!(docker.with_request_timeout(None).unwrap().timeout, None);
+
+        match docker.with_request_timeout(Some(Duration::ZERO)) {
+            Err(Error::InvalidTimeout(timeout)) if timeout.is_zero() => {}
+            Err(e) => panic!("Expected Error::InvalidTimeout, got {}", e),
+            Ok(_) => panic!("Expected Error::InvalidTimeout, got a Docker instance"),
+        }
+    }
+
+    #[test]
+    fn rejects_missing_authority() {
+        #[cfg(unix)]
+        assert!(matches!(
+            Docker::new("unix://"),
+            Err(Error::MissingAuthority)
+        ));
+        assert!(matches!(
+            Docker::new("tcp://"),
+            Err(Error::MissingAuthority)
+        ));
+    }
+
+    #[cfg(feature = "tls")]
+    #[test]
+    fn tls_invalid_cert_path() {
+        let tmp = std::env::temp_dir().join("docker-api-missing-certs");
+        match Docker::tls("127.0.0.1:2376", &tmp, true) {
+            Err(Error::Error(_)) => {}
+            Err(e) => panic!("Expected certificate error, got {}", e),
+            Ok(_) => panic!("Expected certificate error, got a Docker instance"),
+        }
+
+        // https is only supported through the explicit constructor
+        match Docker::new("https://127.0.0.1:2376") {
+            Err(Error::UnsupportedScheme(scheme)) if scheme == "https" => {}
+            Err(e) => panic!(r#"Expected Error::UnsupportedScheme("https"), got {}"#, e),
+            Ok(_) => panic!("Expected Error::UnsupportedScheme, got a Docker instance"),
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_idempotent_requests() {
+        let server = MockServer::start_raw_sequence(["", "", OK]).await;
+        let docker = Docker::builder(server.uri())
+            .retry(3, Duration::from_millis(1))
+            .build()
+            .unwrap();
+
+        let response = docker.get("/_ping").await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body.as_ref(), b"OK");
+        assert_eq!(server.requests().len(), 3);
+
+        let server = MockServer::start_raw_sequence(["", "", OK]).await;
+        let docker = Docker::builder(server.uri())
+            .retry(2, Duration::from_millis(1))
+            .build()
+            .unwrap();
+        assert!(docker.get("/_ping").await.is_err());
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn never_retries_post_requests() {
+        let server = MockServer::start_raw_sequence(["", OK]).await;
+        let docker = Docker::builder(server.uri())
+            .retry(3, Duration::from_millis(1))
+            .build()
+            .unwrap();
+
+        let result = docker
+            .post_string("/containers/test/start", Payload::empty(), None)
+            .await;
+        assert!(result.is_err());
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn raw_requests() {
+        let server = MockServer::start("200 OK", "OK").await;
+        let docker = server.docker();
+
+        let body = docker.get_raw("/_ping").await.unwrap();
+        assert_eq!(&body[..], b"OK");
+
+        let body = docker
+            .post_raw("/build/prune", Payload::empty())
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"OK");
+
+        let response = docker
+            .request(Method::DELETE, "/images/test", Payload::empty())
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+
+        assert!(docker
+            .request(Method::PATCH, "/images/test", Payload::empty())
+            .await
+            .is_err());
+
+        assert_eq!(
+            paths(&server),
+            vec!["/v1.41/_ping", "/v1.41/build/prune", "/v1.41/images/test"]
+        );
+    }
+
+    #[tokio::test]
+    async fn configures_connection_pool() {
+        let server = MockServer::start("200 OK", "OK").await;
+        let docker = Docker::builder(server.uri())
+            .pool_max_idle_per_host(0)
+            .pool_idle_timeout(Duration::from_secs(1))
+            .build()
+            .unwrap();
+
+        for _ in 0..2 {
+            assert_eq!(&docker.get_raw("/_ping").await.unwrap()[..], b"OK");
+        }
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn negotiates_api_version() {
+        let server = MockServer::start("200 OK", r#"{"ApiVersion":"1.39"}"#).await;
+        let mut docker = server.docker();
+
+        docker.negotiate_version().await.unwrap();
+        assert_eq!(docker.version, ApiVersion::new(1, Some(39), None));
+
+        let _ = docker.get_json::<serde_json::Value>("/info").await.unwrap();
+        assert_eq!(paths(&server), vec!["/version", "/v1.39/info"]);
+
+        // a newer server version doesn't override the client version
+        let server = MockServer::start("200 OK", r#"{"ApiVersion":"1.43"}"#).await;
+        let mut docker = server.docker();
+        docker.negotiate_version().await.unwrap();
+        assert_eq!(docker.version, crate::LATEST_API_VERSION);
+    }
+
+    #[test]
+    fn builds_configured_docker() {
+        let docker = Docker::builder("tcp://127.0.0.1:80")
+            .api_version(ApiVersion::new(1, Some(40), None))
+            .request_timeout(Duration::from_secs(5))
+            .build()
+            .unwrap();
+        assert_eq!(docker.version.make_endpoint("/info"), "/v1.40/info");
+        assert_eq!(docker.timeout, Some(Duration::from_secs(5)));
+
+        match Docker::builder("tcp://127.0.0.1:80")
+            .request_timeout(Duration::ZERO)
+            .build()
+        {
+            Err(Error::InvalidTimeout(timeout)) if timeout.is_zero() => {}
+            Err(e) => panic!("Expected Error::InvalidTimeout, got {}", e),
+            Ok(_) => panic!("Expected Error::InvalidTimeout, got a Docker instance"),
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_default_headers() {
+        let server = MockServer::start("200 OK", "{}").await;
+        let docker = Docker::builder(server.uri())
+            .default_header("X-Proxy-Token", "secret")
+            .default_header("User-Agent", "docker-api-test")
+            .build()
+            .unwrap();
+
+        docker.get_json::<serde_json::Value>("/info").await.unwrap();
+        docker
+            .post_string("/containers/test/start", Payload::empty(), None)
+            .await
+            .unwrap();
+        docker
+            .request(Method::DELETE, "/images/test", Payload::empty())
+            .await
+            .unwrap();
+
+        let requests = server.recorded_requests();
+        assert_eq!(requests.len(), 3);
+        for request in requests {
+            assert_eq!(request.header("x-proxy-token"), Some("secret"));
+            assert_eq!(request.header("user-agent"), Some("docker-api-test"));
+        }
+
+        assert!(matches!(
+            Docker::builder(server.uri())
+                .default_header("X Proxy", "secret")
+                .build(),
+            Err(Error::Error(_))
+        ));
+    }
+
+    #[test]
+    fn reads_env_config() {
+        let lookup = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+
+        let config = EnvConfig::from_lookup(lookup(&[("DOCKER_HOST", "")])).unwrap();
+        assert_eq!(
+            config,
+            EnvConfig {
+                host: "unix:///var/run/docker.sock".into(),
+                version: crate::LATEST_API_VERSION,
+                tls_cert_path: None,
+            }
+        );
+        assert_eq!(config.tls_host(), None);
+
+        let config = EnvConfig::from_lookup(lookup(&[
+            ("DOCKER_HOST", "tcp://10.0.0.2:2376"),
+            ("DOCKER_TLS_VERIFY", "1"),
+            ("DOCKER_CERT_PATH", "/etc/docker/certs"),
+            ("DOCKER_API_VERSION", "1.40"),
+        ]))
+        .unwrap();
+        assert_eq!(config.version, ApiVersion::new(1, Some(40), None));
+        assert_eq!(
+            config.tls_cert_path.as_deref(),
+            Some(std::path::Path::new("/etc/docker/certs"))
+        );
+        assert_eq!(config.tls_host(), Some("10.0.0.2:2376"));
+
+        let config = EnvConfig::from_lookup(lookup(&[
+            ("DOCKER_HOST", "unix:///run/docker.sock"),
+            ("DOCKER_TLS_VERIFY", "1"),
+            ("HOME", "/home/user"),
+        ]))
+        .unwrap();
+        assert_eq!(
+            config.tls_cert_path.as_deref(),
+            Some(std::path::Path::new("/home/user/.docker"))
+        );
+        assert_eq!(config.tls_host(), None);
+
+        let config = EnvConfig::from_lookup(lookup(&[
+            ("DOCKER_HOST", "ssh://user@docker.example.com"),
+            ("DOCKER_TLS_VERIFY", "1"),
+        ]))
+        .unwrap();
+        assert_eq!(config.host, "ssh://user@docker.example.com");
+        assert_eq!(config.tls_host(), None);
+
+        assert!(matches!(
+            EnvConfig::from_lookup(lookup(&[("DOCKER_API_VERSION", "latest")])),
+            Err(Error::MalformedVersion(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn connects_env_config() {
+        let server = MockServer::start("200 OK", "OK").await;
+        let mut vars = std::collections::HashMap::new();
+        vars.insert("DOCKER_HOST", server.uri());
+        vars.insert("DOCKER_API_VERSION", "1.40".to_string());
+        let docker = EnvConfig::from_lookup(|name| vars.get(name).cloned())
+            .unwrap()
+            .connect()
+            .unwrap();
+        assert_eq!(&docker.get_raw("/_ping").await.unwrap()[..], b"OK");
+        assert_eq!(paths(&server), vec!["/v1.40/_ping"]);
+
+        let certs = std::env::temp_dir().join("docker-api-missing-certs");
+        vars.insert("DOCKER_TLS_VERIFY", "1".to_string());
+        vars.insert("DOCKER_CERT_PATH", certs.display().to_string());
+        let result = EnvConfig::from_lookup(|name| vars.get(name).cloned())
+            .unwrap()
+            .connect();
+        #[cfg(feature = "tls")]
+        assert!(matches!(result, Err(Error::Error(_))));
+        #[cfg(not(feature = "tls"))]
+        assert!(matches!(result, Err(Error::StringError(_))));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn connects_ssh_env_config() {
+        let config = |host: &'static str| {
+            EnvConfig::from_lookup(move |name| (name == "DOCKER_HOST").then(|| host.to_string()))
+                .unwrap()
+        };
+
+        assert!(matches!(
+            config("ssh://user@docker.example.com").connect(),
+            Err(Error::StringError(_))
+        ));
+        // The URL is validated before the tunnel is opened, so no `ssh` process is started
+        assert!(matches!(
+            config("ssh://").connect_async().await,
+            Err(Error::MissingAuthority) | Err(Error::InvalidUrl(_))
+        ));
+        let server = MockServer::start("200 OK", "OK").await;
+        let docker = EnvConfig::from_lookup(|name| (name == "DOCKER_HOST").then(|| server.uri()))
+            .unwrap()
+            .connect_async()
+            .await
+            .unwrap();
+        assert_eq!(&docker.get_raw("/_ping").await.unwrap()[..], b"OK");
+    }
 }
@@ -24,6 +24,28 @@ pub enum Error {
     InvalidResponse(String),
     #[error("error {code} - {message}")]
     Fault { code: StatusCode, message: String },
+    #[error("The resource is already in the requested state")]
+    NotModified,
+    #[error("Invalid container name `{0}`, the name must match `[a-zA-Z0-9][a-zA-Z0-9_.-]+`")]
+    InvalidContainerName(String),
+    #[error("Invalid platform `{0}`, expected format `os/arch[/variant]`")]
+    InvalidPlatform(String),
+    #[error("Invalid network options - {0}")]
+    InvalidNetworkOpts(String),
+    #[error("Invalid container options - {0}")]
+    InvalidContainerOpts(String),
+    #[error("Invalid pull options - {0}")]
+    InvalidPullOpts(String),
+    #[error("The docker host reported warnings - {}", .0.join("; "))]
+    Warnings(Vec<String>),
+    #[error("The request timed out")]
+    Timeout,
+    #[error("Failed to pull image `{image}` - {message}")]
+    ImagePull { image: String, message: String },
+    #[error("The container is unhealthy - {0}")]
+    Unhealthy(String),
+    #[error("Invalid request timeout - {0:?}")]
+    InvalidTimeout(std::time::Duration),
     #[error("The HTTP connection was not upgraded by the docker host")]
     ConnectionNotUpgraded,
     #[error("Provided scheme `{0}` is not supported")]
@@ -38,6 +60,10 @@ pub enum Error {
     InvalidPort(String),
     #[error("Invalid protocol - {0}")]
     InvalidProtocol(String),
+    #[error("Invalid signal `{0}`")]
+    InvalidSignal(String),
+    #[error("Invalid detach keys `{0}`, expected comma separated keys like `ctrl-p,ctrl-q`")]
+    InvalidDetachKeys(String),
     #[error(transparent)]
     MalformedVersion(#[from] containers_api::version::Error),
     #[error(transparent)]
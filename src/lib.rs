@@ -36,11 +36,19 @@ pub mod models;
 pub mod conn {
     //! Connection related items
     pub(crate) use containers_api::conn::*;
-    pub use containers_api::conn::{Error, Transport, TtyChunk};
+    pub use containers_api::conn::{Error, Payload, Transport, TtyChunk};
 }
 pub mod docker;
 pub mod errors;
 pub mod opts;
+mod path;
+#[cfg(unix)]
+mod ssh;
+mod stream;
+
+#[cfg(any(test, feature = "test-util"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+pub mod testutil;
 
 pub use containers_api::id::Id;
 pub use containers_api::version::ApiVersion;
@@ -53,7 +61,7 @@ pub use crate::{
         network::{self, Network, Networks},
         volume::{self, Volume, Volumes},
     },
-    docker::Docker,
+    docker::{Docker, DockerBuilder},
     errors::{Error, Result},
 };
 
@@ -3,11 +3,13 @@
 pub use docker_api_stubs::models::*;
 
 use crate::errors::{Error, Result};
+use crate::opts::{NetworkDriver, Protocol, Scope};
 
 use hyper::header::HeaderMap;
 use serde::{Deserialize, Serialize};
 
 use std::convert::TryFrom;
+use std::net::{Ipv4Addr, Ipv6Addr};
 
 #[derive(Serialize, Debug)]
 pub struct PingInfo {
@@ -82,6 +84,35 @@ pub enum ImageBuildChunk {
         #[serde(rename = "progressDetail")]
         progress_detail: Option<ProgressDetail>,
     },
+    /// Progress of a build using BuildKit. The `id` is `moby.buildkit.trace` and `aux` holds a
+    /// base64 encoded `StatusResponse` protobuf message of the BuildKit control API.
+    BuildKitTrace {
+        id: String,
+        aux: String,
+    },
+}
+
+impl ImageBuildChunk {
+    /// Returns the decoded protobuf message of a
+    /// [`BuildKitTrace`](ImageBuildChunk::BuildKitTrace) chunk or `None` for any other chunk.
+    pub fn buildkit_trace(&self) -> Option<Result<Vec<u8>>> {
+        match self {
+            ImageBuildChunk::BuildKitTrace { aux, .. } => {
+                Some(base64::decode(aux).map_err(|e| Error::InvalidResponse(e.to_string())))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the digest reported by the registry while pulling an image, for example
+    /// `sha256:8914eb54...`. Pulls report the digest once with a `Digest: <digest>` status, this
+    /// returns `None` for any other chunk.
+    pub fn pulled_digest(&self) -> Option<&str> {
+        match self {
+            ImageBuildChunk::PullStatus { status, .. } => status.strip_prefix("Digest: "),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -102,3 +133,945 @@ pub struct ProgressDetail {
 }
 
 pub type Labels = std::collections::HashMap<String, String>;
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(try_from = "u8", into = "u8")]
+/// Kind of a change made to the filesystem of a container.
+pub enum ChangeKind {
+    Modified = 0,
+    Added = 1,
+    Deleted = 2,
+}
+
+impl TryFrom<u8> for ChangeKind {
+    type Error = String;
+
+    fn try_from(kind: u8) -> std::result::Result<Self, Self::Error> {
+        match kind {
+            0 => Ok(ChangeKind::Modified),
+            1 => Ok(ChangeKind::Added),
+            2 => Ok(ChangeKind::Deleted),
+            kind => Err(format!("unknown filesystem change kind `{}`", kind)),
+        }
+    }
+}
+
+impl From<ChangeKind> for u8 {
+    fn from(kind: ChangeKind) -> Self {
+        kind as u8
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+/// A change made to the filesystem of a container as returned by
+/// [`Container::changes`](crate::Container::changes).
+pub struct FilesystemChange {
+    pub path: String,
+    pub kind: ChangeKind,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+/// Processes running in a container as returned by [`Container::top`](crate::Container::top).
+pub struct ContainerTop {
+    #[serde(default)]
+    /// The ps column titles.
+    pub titles: Vec<String>,
+    #[serde(default)]
+    /// Each process running in the container, where each process is an array of values
+    /// corresponding to the titles.
+    pub processes: Vec<Vec<String>>,
+}
+
+impl ContainerTop {
+    /// Returns an iterator over the processes where each process maps the column titles to its
+    /// values.
+    pub fn rows(&self) -> impl Iterator<Item = std::collections::HashMap<String, String>> + '_ {
+        self.processes.iter().map(move |process| {
+            self.titles
+                .iter()
+                .cloned()
+                .zip(process.iter().cloned())
+                .collect()
+        })
+    }
+}
+
+/// Implements conversions and `Deref` for a wrapper of an inspected object that keeps the
+/// fields unknown to this version of the library.
+macro_rules! impl_details {
+    ($details:ident => $inspect:ident, $field:ident) => {
+        impl $details {
+            /// Returns the inspected object without the unknown fields.
+            pub fn into_inner(self) -> $inspect {
+                self.$field
+            }
+        }
+
+        impl From<$inspect> for $details {
+            fn from($field: $inspect) -> Self {
+                Self {
+                    $field,
+                    extra: Default::default(),
+                }
+            }
+        }
+
+        impl std::ops::Deref for $details {
+            type Target = $inspect;
+
+            fn deref(&self) -> &Self::Target {
+                &self.$field
+            }
+        }
+    };
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+/// Details of an image as returned by [`Image::details`](crate::Image::details) with helpers for
+/// commonly used values. Fields of the inspected image are accessible through `Deref`.
+///
+/// ```no_run
+/// # async fn example(docker: docker_api::Docker) -> docker_api::Result<()> {
+/// let details = docker.images().get("nginx").details().await?;
+/// println!("{} {:?}", details.short_id(), details.primary_tag());
+/// # Ok(())
+/// # }
+/// ```
+pub struct ImageDetails {
+    #[serde(flatten)]
+    inspect: ImageInspect,
+    #[serde(flatten)]
+    /// Fields returned by the docker host that are unknown to this version of the library.
+    pub extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+impl_details!(ImageDetails => ImageInspect, inspect);
+
+impl ImageDetails {
+    /// Returns all tags of the image including dangling `<none>:<none>` tags.
+    pub fn repo_tags(&self) -> &[String] {
+        self.inspect.repo_tags.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the first tag of the image that is not a dangling `<none>:<none>` tag.
+    pub fn primary_tag(&self) -> Option<&str> {
+        self.repo_tags()
+            .iter()
+            .map(String::as_str)
+            .find(|tag| !tag.contains("<none>"))
+    }
+
+    /// Returns the first 12 characters of the image ID without the `sha256:` prefix, like the
+    /// docker CLI displays it. Returns an empty string if the ID is missing.
+    pub fn short_id(&self) -> &str {
+        let id = self.inspect.id.as_deref().unwrap_or_default();
+        let id = id.strip_prefix("sha256:").unwrap_or(id);
+        id.get(..12).unwrap_or(id)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+/// Details of a network as returned by [`Network::details`](crate::Network::details) with the
+/// scope and driver as typed values. Fields of the inspected network are accessible through
+/// `Deref`.
+pub struct NetworkDetails {
+    #[serde(flatten)]
+    network: Network,
+    #[serde(flatten)]
+    /// Fields returned by the docker host that are unknown to this version of the library.
+    pub extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+impl_details!(NetworkDetails => Network, network);
+
+impl NetworkDetails {
+    /// Returns the scope of the network, `None` if it wasn't reported.
+    pub fn scope(&self) -> Option<Scope> {
+        self.network.scope.clone().map(Scope::from)
+    }
+
+    /// Returns the driver of the network, `None` if it wasn't reported.
+    pub fn driver(&self) -> Option<NetworkDriver> {
+        self.network.driver.clone().map(NetworkDriver::from)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+/// Details of a container as returned by [`Container::details`](crate::Container::details).
+/// Fields of the inspected container are accessible through `Deref`.
+pub struct ContainerDetails {
+    #[serde(flatten)]
+    inspect: ContainerInspect200Response,
+    #[serde(flatten)]
+    /// Fields returned by the docker host that are unknown to this version of the library.
+    pub extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+impl_details!(ContainerDetails => ContainerInspect200Response, inspect);
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+/// Information about a file or directory inside of a container as returned by
+/// [`Container::stat_path`](crate::Container::stat_path).
+pub struct PathStat {
+    pub name: String,
+    pub size: i64,
+    /// File mode bits as defined by Go's `os.FileMode`.
+    pub mode: u32,
+    pub mtime: String,
+    pub link_target: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+/// Result of waiting for a container as returned by [`Container::wait`](crate::Container::wait).
+pub struct ContainerWaitInfo {
+    #[serde(rename = "StatusCode")]
+    /// Exit code of the container.
+    pub status_code: i64,
+    #[serde(rename = "Error")]
+    /// Set if the daemon failed to wait for the container. This is unrelated to the exit code.
+    pub error: Option<ContainerWaitExitError>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Outcome of stopping a container with [`Container::stop`](crate::Container::stop).
+pub enum StopOutcome {
+    /// The container was running and is now stopped.
+    Stopped,
+    /// The container was not running so nothing was done.
+    AlreadyStopped,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// A container port together with the host address it is published on, parsed from the
+/// [`PortMap`] of the container's network settings.
+pub struct PortMapping {
+    pub container_port: u16,
+    pub protocol: Protocol,
+    /// Host IP the port is published on, `None` if the port is only exposed.
+    pub host_ip: Option<String>,
+    /// Host port the port is published on, `None` if the port is only exposed.
+    pub host_port: Option<u16>,
+}
+
+impl PortMapping {
+    /// Converts the ports of `settings` to a list of mappings sorted by the container port.
+    /// A port published on multiple host addresses results in one mapping per address.
+    pub fn from_settings(settings: &NetworkSettings) -> Result<Vec<PortMapping>> {
+        settings
+            .ports
+            .as_ref()
+            .map(Self::from_port_map)
+            .unwrap_or_else(|| Ok(Vec::new()))
+    }
+
+    /// Converts a raw [`PortMap`] with keys like `80/tcp` to a list of mappings sorted by the
+    /// container port.
+    pub fn from_port_map(ports: &PortMap) -> Result<Vec<PortMapping>> {
+        let mut mappings = Vec::new();
+        for (port, bindings) in ports {
+            let (container_port, protocol) = port
+                .split_once('/')
+                .ok_or_else(|| Error::InvalidPort(format!("missing protocol in `{}`", port)))?;
+            let container_port = container_port
+                .parse::<u16>()
+                .map_err(|e| Error::InvalidPort(format!("invalid port `{}` - {}", port, e)))?;
+            let protocol = protocol.parse::<Protocol>()?;
+
+            let bindings = bindings.as_deref().unwrap_or_default();
+            if bindings.is_empty() {
+                mappings.push(PortMapping {
+                    container_port,
+                    protocol,
+                    host_ip: None,
+                    host_port: None,
+                });
+            }
+            for binding in bindings {
+                let host_port = match binding.host_port.as_deref() {
+                    None | Some("") => None,
+                    Some(host_port) => Some(host_port.parse::<u16>().map_err(|e| {
+                        Error::InvalidPort(format!("invalid host port `{}` - {}", host_port, e))
+                    })?),
+                };
+                mappings.push(PortMapping {
+                    container_port,
+                    protocol,
+                    host_ip: binding.host_ip.clone().filter(|ip| !ip.is_empty()),
+                    host_port,
+                });
+            }
+        }
+        mappings.sort();
+        Ok(mappings)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// Addresses of a container on a network parsed from its [`EndpointSettings`]. Docker reports
+/// unset addresses as empty strings, these are converted to `None`.
+pub struct EndpointAddresses {
+    pub ipv4: Option<Ipv4Addr>,
+    pub gateway: Option<Ipv4Addr>,
+    pub global_ipv6: Option<Ipv6Addr>,
+    pub ipv6_gateway: Option<Ipv6Addr>,
+}
+
+impl From<&EndpointSettings> for EndpointAddresses {
+    fn from(settings: &EndpointSettings) -> Self {
+        fn parse<T: std::str::FromStr>(addr: &Option<String>) -> Option<T> {
+            addr.as_deref().and_then(|addr| addr.parse().ok())
+        }
+
+        EndpointAddresses {
+            ipv4: parse(&settings.ip_address),
+            gateway: parse(&settings.gateway),
+            global_ipv6: parse(&settings.global_i_pv_6_address),
+            ipv6_gateway: parse(&settings.i_pv_6_gateway),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+/// Summary of a swarm task as returned when listing tasks.
+pub struct TaskInfo {
+    #[serde(rename = "ID")]
+    /// The ID of the task.
+    pub id: String,
+    #[serde(rename = "ServiceID")]
+    /// The ID of the service this task is part of.
+    pub service_id: Option<String>,
+    #[serde(rename = "NodeID")]
+    /// The ID of the node this task is scheduled on.
+    pub node_id: Option<String>,
+    #[serde(rename = "Status")]
+    pub status: Option<TaskInfoStatus>,
+    #[serde(rename = "DesiredState")]
+    pub desired_state: Option<TaskState>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct TaskInfoStatus {
+    #[serde(rename = "State")]
+    pub state: Option<TaskState>,
+    #[serde(rename = "Message")]
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+/// Response of creating a network.
+pub struct NetworkCreateInfo {
+    #[serde(rename = "Id")]
+    /// The ID of the created network.
+    pub id: String,
+    #[serde(rename = "Warning")]
+    /// Warnings encountered when creating the network, one per line.
+    pub warning: Option<String>,
+}
+
+impl NetworkCreateInfo {
+    /// Returns the warnings reported by the daemon, skipping empty lines.
+    pub fn warnings(&self) -> Vec<&str> {
+        self.warning
+            .as_deref()
+            .unwrap_or_default()
+            .lines()
+            .map(str::trim)
+            .filter(|warning| !warning.is_empty())
+            .collect()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+/// Endpoint of a container connected to a network as listed when inspecting the network.
+pub struct NetworkContainerDetails {
+    #[serde(rename = "Name", default)]
+    pub name: String,
+    #[serde(rename = "EndpointID", default)]
+    pub endpoint_id: String,
+    #[serde(rename = "MacAddress", default)]
+    pub mac_address: String,
+    #[serde(rename = "IPv4Address", default)]
+    /// IPv4 address of the endpoint in CIDR notation, for example `172.18.0.2/16`.
+    pub ipv4_address: String,
+    #[serde(rename = "IPv6Address", default)]
+    /// IPv6 address of the endpoint in CIDR notation, empty if IPv6 is disabled.
+    pub ipv6_address: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+/// A single snapshot of resource usage statistics of a container as returned by
+/// [`Container::stats`](crate::Container::stats).
+pub struct ContainerStats {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub read: Option<String>,
+    pub preread: Option<String>,
+    pub num_procs: Option<u32>,
+    pub pids_stats: Option<PidsStats>,
+    pub cpu_stats: Option<CpuStats>,
+    pub precpu_stats: Option<CpuStats>,
+    pub memory_stats: Option<MemoryStats>,
+    pub blkio_stats: Option<BlkioStats>,
+    pub networks: Option<std::collections::HashMap<String, NetworkStats>>,
+}
+
+impl ContainerStats {
+    /// Calculates the CPU usage of the container in percents the same way the docker CLI does,
+    /// that is by comparing the current and previous reading of the CPU counters. Returns `0.0`
+    /// if there is not enough data to calculate the value.
+    pub fn cpu_percentage(&self) -> f64 {
+        let (cpu, precpu) = match (&self.cpu_stats, &self.precpu_stats) {
+            (Some(cpu), Some(precpu)) => (cpu, precpu),
+            _ => return 0.0,
+        };
+
+        let cpu_delta = cpu.cpu_usage.total_usage as f64 - precpu.cpu_usage.total_usage as f64;
+        let system_delta = cpu.system_cpu_usage.unwrap_or_default() as f64
+            - precpu.system_cpu_usage.unwrap_or_default() as f64;
+        let online_cpus = cpu.online_cpus.map(|n| n as f64).unwrap_or_else(|| {
+            cpu.cpu_usage
+                .percpu_usage
+                .as_ref()
+                .map(|usage| usage.len() as f64)
+                .unwrap_or_default()
+        });
+
+        if cpu_delta > 0.0 && system_delta > 0.0 {
+            (cpu_delta / system_delta) * online_cpus * 100.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the memory used by the container without the page cache, as displayed by the
+    /// docker CLI. The inactive file cache is subtracted on both cgroups v1 and v2 hosts, older
+    /// daemons only reporting `cache` have that value subtracted instead.
+    pub fn memory_usage(&self) -> Option<u64> {
+        let memory = self.memory_stats.as_ref()?;
+        let usage = memory.usage?;
+        let cache = memory
+            .stats
+            .as_ref()
+            .and_then(|stats| {
+                ["total_inactive_file", "inactive_file", "cache"]
+                    .iter()
+                    .find_map(|key| stats.get(*key))
+            })
+            .copied()
+            .unwrap_or_default();
+
+        Some(usage.saturating_sub(cache))
+    }
+
+    /// Returns the memory limit of the container, the memory of the host if no limit is set.
+    pub fn memory_limit(&self) -> Option<u64> {
+        self.memory_stats.as_ref()?.limit
+    }
+
+    /// Calculates the memory usage relative to the limit in percents. Returns `0.0` if there is
+    /// not enough data to calculate the value.
+    pub fn memory_percentage(&self) -> f64 {
+        match (self.memory_usage(), self.memory_limit()) {
+            (Some(usage), Some(limit)) if limit > 0 => usage as f64 / limit as f64 * 100.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Returns the total number of received and transmitted bytes summed across all network
+    /// interfaces of the container.
+    pub fn network_totals(&self) -> (u64, u64) {
+        self.networks
+            .iter()
+            .flat_map(|networks| networks.values())
+            .fold((0, 0), |(rx, tx), network| {
+                (rx + network.rx_bytes, tx + network.tx_bytes)
+            })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PidsStats {
+    pub current: Option<u64>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct CpuStats {
+    pub cpu_usage: CpuUsage,
+    pub system_cpu_usage: Option<u64>,
+    pub online_cpus: Option<u32>,
+    pub throttling_data: Option<ThrottlingData>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct CpuUsage {
+    #[serde(default)]
+    pub total_usage: u64,
+    /// Not available on hosts using cgroups v2.
+    pub percpu_usage: Option<Vec<u64>>,
+    #[serde(default)]
+    pub usage_in_kernelmode: u64,
+    #[serde(default)]
+    pub usage_in_usermode: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ThrottlingData {
+    pub periods: u64,
+    pub throttled_periods: u64,
+    pub throttled_time: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct MemoryStats {
+    pub usage: Option<u64>,
+    pub max_usage: Option<u64>,
+    pub limit: Option<u64>,
+    pub failcnt: Option<u64>,
+    /// Detailed memory statistics, the keys differ between cgroups v1 and v2.
+    pub stats: Option<std::collections::HashMap<String, u64>>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct BlkioStats {
+    pub io_service_bytes_recursive: Option<Vec<BlkioStat>>,
+    pub io_serviced_recursive: Option<Vec<BlkioStat>>,
+    pub io_queue_recursive: Option<Vec<BlkioStat>>,
+    pub io_service_time_recursive: Option<Vec<BlkioStat>>,
+    pub io_wait_time_recursive: Option<Vec<BlkioStat>>,
+    pub io_merged_recursive: Option<Vec<BlkioStat>>,
+    pub io_time_recursive: Option<Vec<BlkioStat>>,
+    pub sectors_recursive: Option<Vec<BlkioStat>>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct BlkioStat {
+    pub major: u64,
+    pub minor: u64,
+    pub op: String,
+    pub value: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+/// Counters of a network interface. Counters not reported by the docker host, for example on
+/// Windows, are `0`.
+pub struct NetworkStats {
+    pub rx_bytes: u64,
+    pub rx_packets: u64,
+    pub rx_errors: u64,
+    pub rx_dropped: u64,
+    pub tx_bytes: u64,
+    pub tx_packets: u64,
+    pub tx_errors: u64,
+    pub tx_dropped: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_build_chunk_decode() {
+        let chunk: ImageBuildChunk =
+            serde_json::from_str(r#"{"stream":"Step 1/2 : FROM alpine\n"}"#).unwrap();
+        assert!(matches!(chunk, ImageBuildChunk::Update { stream } if stream.starts_with("Step")));
+
+        let chunk: ImageBuildChunk =
+            serde_json::from_str(r#"{"aux":{"ID":"sha256:e0d3f"}}"#).unwrap();
+        assert!(matches!(chunk, ImageBuildChunk::Digest { aux } if aux.id == "sha256:e0d3f"));
+
+        let chunk: ImageBuildChunk = serde_json::from_str(&format!(
+            r#"{{"id":"moby.buildkit.trace","aux":"{}"}}"#,
+            base64::encode([0x0a, 0x02, 0x08, 0x01])
+        ))
+        .unwrap();
+        match &chunk {
+            ImageBuildChunk::BuildKitTrace { id, .. } => assert_eq!(id, "moby.buildkit.trace"),
+            chunk => panic!("Expected ImageBuildChunk::BuildKitTrace, got {:?}", chunk),
+        }
+        assert_eq!(
+            chunk.buildkit_trace().unwrap().unwrap(),
+            vec![0x0a, 0x02, 0x08, 0x01]
+        );
+
+        let chunk: ImageBuildChunk =
+            serde_json::from_str(r#"{"id":"moby.buildkit.trace","aux":"not base64!"}"#).unwrap();
+        assert!(matches!(
+            chunk.buildkit_trace(),
+            Some(Err(Error::InvalidResponse(_)))
+        ));
+        assert_eq!(chunk.pulled_digest(), None);
+
+        let chunk: ImageBuildChunk =
+            serde_json::from_str(r#"{"status":"Digest: sha256:8914eb54f968"}"#).unwrap();
+        assert_eq!(chunk.pulled_digest(), Some("sha256:8914eb54f968"));
+        let chunk: ImageBuildChunk =
+            serde_json::from_str(r#"{"status":"Pulling from library/alpine","id":"3.17"}"#)
+                .unwrap();
+        assert_eq!(chunk.pulled_digest(), None);
+    }
+
+    const STATS: &str = r#"{
+        "read": "2022-12-20T10:00:01.000000000Z",
+        "preread": "2022-12-20T10:00:00.000000000Z",
+        "id": "0123456789ab",
+        "name": "/test",
+        "num_procs": 0,
+        "pids_stats": {"current": 3},
+        "cpu_stats": {
+            "cpu_usage": {"total_usage": 400, "usage_in_kernelmode": 100, "usage_in_usermode": 300},
+            "system_cpu_usage": 2000,
+            "online_cpus": 2
+        },
+        "precpu_stats": {
+            "cpu_usage": {"total_usage": 200, "percpu_usage": [100, 100], "usage_in_kernelmode": 50, "usage_in_usermode": 150},
+            "system_cpu_usage": 1000,
+            "online_cpus": 2
+        },
+        "memory_stats": {"usage": 1000, "limit": 4000, "stats": {"cache": 200}},
+        "blkio_stats": {"io_service_bytes_recursive": [{"major": 8, "minor": 0, "op": "Read", "value": 1024}]},
+        "networks": {
+            "eth0": {"rx_bytes": 10, "rx_packets": 1, "rx_errors": 0, "rx_dropped": 0, "tx_bytes": 20, "tx_packets": 2, "tx_errors": 0, "tx_dropped": 0},
+            "eth1": {"rx_bytes": 5, "rx_packets": 1, "rx_errors": 0, "rx_dropped": 0, "tx_bytes": 7, "tx_packets": 1, "tx_errors": 0, "tx_dropped": 0}
+        }
+    }"#;
+
+    #[test]
+    fn port_mappings() {
+        let ports: PortMap = serde_json::from_str(
+            r#"{
+                "80/tcp": [{"HostIp": "0.0.0.0", "HostPort": "8080"}, {"HostIp": "::", "HostPort": "8080"}],
+                "53/udp": null
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            PortMapping::from_port_map(&ports).unwrap(),
+            vec![
+                PortMapping {
+                    container_port: 53,
+                    protocol: Protocol::Udp,
+                    host_ip: None,
+                    host_port: None,
+                },
+                PortMapping {
+                    container_port: 80,
+                    protocol: Protocol::Tcp,
+                    host_ip: Some("0.0.0.0".into()),
+                    host_port: Some(8080),
+                },
+                PortMapping {
+                    container_port: 80,
+                    protocol: Protocol::Tcp,
+                    host_ip: Some("::".into()),
+                    host_port: Some(8080),
+                },
+            ]
+        );
+
+        let ports: PortMap = serde_json::from_str(r#"{"80": null}"#).unwrap();
+        assert!(matches!(
+            PortMapping::from_port_map(&ports),
+            Err(Error::InvalidPort(_))
+        ));
+    }
+
+    #[test]
+    fn endpoint_addresses() {
+        let settings: EndpointSettings = serde_json::from_str(
+            r#"{
+                "IPAddress": "172.18.0.2",
+                "Gateway": "172.18.0.1",
+                "GlobalIPv6Address": "2001:db8::2",
+                "IPv6Gateway": "2001:db8::1"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            EndpointAddresses::from(&settings),
+            EndpointAddresses {
+                ipv4: Some(Ipv4Addr::new(172, 18, 0, 2)),
+                gateway: Some(Ipv4Addr::new(172, 18, 0, 1)),
+                global_ipv6: Some("2001:db8::2".parse().unwrap()),
+                ipv6_gateway: Some("2001:db8::1".parse().unwrap()),
+            }
+        );
+
+        let settings: EndpointSettings = serde_json::from_str(
+            r#"{"IPAddress": "172.18.0.2", "GlobalIPv6Address": "", "IPv6Gateway": ""}"#,
+        )
+        .unwrap();
+        let addresses = EndpointAddresses::from(&settings);
+        assert_eq!(addresses.ipv4, Some(Ipv4Addr::new(172, 18, 0, 2)));
+        assert_eq!(addresses.global_ipv6, None);
+        assert_eq!(addresses.ipv6_gateway, None);
+    }
+
+    #[test]
+    fn network_create_warnings() {
+        let info: NetworkCreateInfo = serde_json::from_str(
+            r#"{"Id":"22be93d5babb","Warning":"first warning\nsecond warning\n"}"#,
+        )
+        .unwrap();
+        assert_eq!(info.warnings(), vec!["first warning", "second warning"]);
+
+        let info: NetworkCreateInfo =
+            serde_json::from_str(r#"{"Id":"22be93d5babb","Warning":""}"#).unwrap();
+        assert!(info.warnings().is_empty());
+
+        let info: NetworkCreateInfo = serde_json::from_str(r#"{"Id":"22be93d5babb"}"#).unwrap();
+        assert!(info.warnings().is_empty());
+    }
+
+    #[test]
+    fn event_message_decode() {
+        let event: EventMessage = serde_json::from_str(
+            r#"{"status":"start","id":"0123456789ab","from":"ubuntu:latest","Type":"container","Action":"start","Actor":{"ID":"0123456789ab","Attributes":{"image":"ubuntu:latest","name":"test"}},"scope":"local","time":1671530400,"timeNano":1671530400123456789}"#,
+        )
+        .unwrap();
+
+        assert_eq!(event.type_.as_deref(), Some("container"));
+        assert_eq!(event.action.as_deref(), Some("start"));
+        let actor = event.actor.unwrap();
+        assert_eq!(actor.id.as_deref(), Some("0123456789ab"));
+        assert_eq!(
+            actor.attributes.unwrap().get("name").map(String::as_str),
+            Some("test")
+        );
+        assert_eq!(event.time, Some(1671530400));
+        assert_eq!(event.time_nano, Some(1671530400123456789));
+    }
+
+    #[test]
+    fn system_data_usage_decode() {
+        let usage: SystemDataUsage200Response = serde_json::from_str(
+            r#"{
+                "LayersSize": 1092588,
+                "Images": [{
+                    "Id": "sha256:2b8fd9751c4c0f5dd266fcae00707e67a2545ef34f9a29354585f93dac906749",
+                    "ParentId": "",
+                    "RepoTags": ["busybox:latest"],
+                    "RepoDigests": [],
+                    "Created": 1466724217,
+                    "Size": 1092588,
+                    "SharedSize": 0,
+                    "VirtualSize": 1092588,
+                    "Labels": {},
+                    "Containers": 1
+                }],
+                "Containers": [{"Id": "e575172ed11dc01bfce087fb27bee502db149e1a0fad7c296ad300bbff178148", "SizeRw": 0, "SizeRootFs": 1092588}],
+                "Volumes": [{
+                    "Name": "my-volume",
+                    "Driver": "local",
+                    "Mountpoint": "/var/lib/docker/volumes/my-volume/_data",
+                    "Labels": {},
+                    "Scope": "local",
+                    "Options": {},
+                    "UsageData": {"Size": 10920104, "RefCount": 2}
+                }],
+                "BuildCache": [{"ID": "hw53o5aio51xtltp5xjp8v7fx", "Type": "regular", "InUse": false, "Shared": true, "Size": 51, "UsageCount": 26}]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(u64::try_from(usage.layers_size.unwrap()).unwrap(), 1092588);
+        let images = usage.images.unwrap();
+        assert_eq!(u64::try_from(images[0].size).unwrap(), 1092588);
+        assert_eq!(usage.containers.unwrap()[0].size_root_fs, Some(1092588));
+        let volume_usage = usage.volumes.unwrap()[0].usage_data.clone().unwrap();
+        assert_eq!(u64::try_from(volume_usage.size).unwrap(), 10920104);
+        assert_eq!(usage.build_cache.unwrap()[0].size, Some(51));
+    }
+
+    #[test]
+    fn ping_info_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("api-version", "1.41".parse().unwrap());
+        headers.insert("docker-experimental", "false".parse().unwrap());
+        headers.insert(
+            "cache-control",
+            "no-cache, no-store, must-revalidate".parse().unwrap(),
+        );
+        headers.insert("pragma", "no-cache".parse().unwrap());
+        headers.insert("ostype", "linux".parse().unwrap());
+        headers.insert("date", "Tue, 20 Dec 2022 10:00:00 GMT".parse().unwrap());
+        headers.insert("server", "Docker/20.10.21 (linux)".parse().unwrap());
+
+        let info = PingInfo::try_from(&headers).unwrap();
+        assert_eq!(info.api_version, "1.41");
+        assert!(!info.docker_experimental);
+        assert!(info.builder_version.is_none());
+        assert!(info
+            .api_version
+            .parse::<containers_api::version::ApiVersion>()
+            .is_ok());
+
+        headers.remove("api-version");
+        assert!(PingInfo::try_from(&headers).is_err());
+    }
+
+    #[test]
+    fn filesystem_changes_decode() {
+        let changes: Vec<FilesystemChange> = serde_json::from_str(
+            r#"[
+                {"Path": "/dev", "Kind": 0},
+                {"Path": "/dev/kmsg", "Kind": 1},
+                {"Path": "/test", "Kind": 2}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            changes.iter().map(|change| change.kind).collect::<Vec<_>>(),
+            vec![ChangeKind::Modified, ChangeKind::Added, ChangeKind::Deleted]
+        );
+        assert_eq!(changes[1].path, "/dev/kmsg");
+
+        let changes: Option<Vec<FilesystemChange>> = serde_json::from_str("null").unwrap();
+        assert!(changes.is_none());
+        assert!(serde_json::from_str::<FilesystemChange>(r#"{"Path": "/", "Kind": 3}"#).is_err());
+    }
+
+    #[test]
+    fn container_top_rows() {
+        let top: ContainerTop = serde_json::from_str(
+            r#"{
+                "Titles": ["UID", "PID", "PPID", "C", "STIME", "TTY", "TIME", "CMD"],
+                "Processes": [
+                    ["root", "13642", "882", "0", "17:03", "pts/0", "00:00:00", "/bin/bash"],
+                    ["root", "13735", "13642", "0", "17:06", "pts/0", "00:00:00", "sleep 10"]
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let rows: Vec<_> = top.rows().collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["PID"], "13642");
+        assert_eq!(rows[1]["PID"], "13735");
+        assert_eq!(rows[1]["CMD"], "sleep 10");
+
+        let top: ContainerTop = serde_json::from_str("{}").unwrap();
+        assert_eq!(top.rows().count(), 0);
+    }
+
+    #[test]
+    fn container_stats_cpu_percentage() {
+        let stats: ContainerStats = serde_json::from_str(STATS).unwrap();
+        assert_eq!(stats.cpu_percentage(), 40.0);
+
+        let stats: ContainerStats = serde_json::from_str(
+            r#"{"cpu_stats": {"cpu_usage": {"total_usage": 0}}, "precpu_stats": {"cpu_usage": {}}}"#,
+        )
+        .unwrap();
+        assert_eq!(stats.cpu_percentage(), 0.0);
+    }
+
+    #[test]
+    fn container_stats_memory_and_network() {
+        let stats: ContainerStats = serde_json::from_str(STATS).unwrap();
+        assert_eq!(stats.memory_usage(), Some(800));
+        assert_eq!(stats.memory_limit(), Some(4000));
+        assert_eq!(stats.memory_percentage(), 20.0);
+        assert_eq!(stats.network_totals(), (15, 27));
+
+        // cgroups v2 reports the inactive file cache instead of the total cache
+        let stats: ContainerStats = serde_json::from_str(
+            r#"{"memory_stats": {"usage": 1000, "limit": 2000, "stats": {"inactive_file": 500}}}"#,
+        )
+        .unwrap();
+        assert_eq!(stats.memory_percentage(), 25.0);
+        assert_eq!(stats.network_totals(), (0, 0));
+
+        let stats: ContainerStats =
+            serde_json::from_str(r#"{"networks": {"eth0": {"rx_bytes": 3}}}"#).unwrap();
+        assert_eq!(stats.network_totals(), (3, 0));
+
+        let stats: ContainerStats = serde_json::from_str(r#"{"memory_stats": {}}"#).unwrap();
+        assert_eq!(stats.memory_usage(), None);
+        assert_eq!(stats.memory_percentage(), 0.0);
+    }
+
+    #[test]
+    fn network_details_scope_and_driver() {
+        let details: NetworkDetails = serde_json::from_str(
+            r#"{"Name": "ingress", "Scope": "swarm", "Driver": "overlay", "Ingress": true}"#,
+        )
+        .unwrap();
+        assert_eq!(details.scope(), Some(Scope::Swarm));
+        assert_eq!(details.driver(), Some(NetworkDriver::Overlay));
+        assert_eq!(details.name.as_deref(), Some("ingress"));
+
+        let details: NetworkDetails =
+            serde_json::from_str(r#"{"Scope": "datacenter", "Driver": "custom/plugin"}"#).unwrap();
+        assert_eq!(details.scope(), Some(Scope::Other("datacenter".into())));
+        assert_eq!(
+            details.driver(),
+            Some(NetworkDriver::Other("custom/plugin".into()))
+        );
+
+        let details = NetworkDetails::from(serde_json::from_str::<Network>("{}").unwrap());
+        assert_eq!(details.scope(), None);
+        assert_eq!(details.driver(), None);
+        assert!(details.extra.is_empty());
+    }
+
+    #[test]
+    fn details_keep_unknown_fields() {
+        let details: ContainerDetails = serde_json::from_str(
+            r#"{"Id": "0123456789ab", "Name": "/web", "ImageManifestDescriptor": {"size": 1}}"#,
+        )
+        .unwrap();
+        assert_eq!(details.id.as_deref(), Some("0123456789ab"));
+        assert_eq!(
+            details.extra,
+            std::collections::HashMap::from([(
+                "ImageManifestDescriptor".to_string(),
+                serde_json::json!({"size": 1})
+            )])
+        );
+        let json = serde_json::to_value(&details).unwrap();
+        assert_eq!(json["ImageManifestDescriptor"]["size"], 1);
+        assert_eq!(json["Name"], "/web");
+
+        let details: ImageDetails =
+            serde_json::from_str(r#"{"Id": "sha256:3b25b682ea82", "Descriptor": {}}"#).unwrap();
+        assert_eq!(details.short_id(), "3b25b682ea82");
+        assert!(details.extra.contains_key("Descriptor"));
+
+        let details: NetworkDetails =
+            serde_json::from_str(r#"{"Name": "bridge", "ConfigOnly": false}"#).unwrap();
+        assert_eq!(details.name.as_deref(), Some("bridge"));
+        assert_eq!(details.extra["ConfigOnly"], false);
+        assert!(!details.extra.contains_key("Name"));
+    }
+
+    #[test]
+    fn image_details_tags() {
+        let details: ImageDetails = serde_json::from_str(
+            r#"{
+                "Id": "sha256:3b25b682ea82b2db3cc4fd48db818be788ee3f902ac7378090cf2624ec2442df",
+                "RepoTags": ["<none>:<none>", "nginx:1.23", "nginx:latest"]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(details.repo_tags().len(), 3);
+        assert_eq!(details.primary_tag(), Some("nginx:1.23"));
+        assert_eq!(details.short_id(), "3b25b682ea82");
+        assert_eq!(details.os, None);
+
+        let details: ImageDetails =
+            serde_json::from_str(r#"{"Id": "3b25b6", "RepoTags": ["<none>:<none>"]}"#).unwrap();
+        assert_eq!(details.primary_tag(), None);
+        assert_eq!(details.short_id(), "3b25b6");
+
+        let details = ImageDetails::from(serde_json::from_str::<ImageInspect>("{}").unwrap());
+        assert!(details.repo_tags().is_empty());
+        assert_eq!(details.short_id(), "");
+    }
+}
@@ -1,9 +1,9 @@
 use crate::models::Labels;
-use crate::opts::ImageName;
+use crate::opts::{EndpointConfig, Filters, ImageName, Timestamp};
 use containers_api::opts::{Filter, FilterItem};
 use containers_api::{
     impl_field, impl_filter_func, impl_map_field, impl_opts_builder, impl_str_field,
-    impl_url_bool_field, impl_url_str_field, impl_vec_field,
+    impl_url_bool_field, impl_url_field, impl_url_str_field, impl_vec_field,
 };
 
 use std::{
@@ -92,6 +92,123 @@ impl AsRef<str> for ContainerStatus {
     }
 }
 
+#[derive(Clone, Copy, Debug, Default)]
+/// The condition to wait for in [`Container::wait`](crate::api::Container::wait).
+pub enum WaitCondition {
+    /// Wait until the container is not running. Returns immediately if the container is
+    /// already stopped.
+    #[default]
+    NotRunning,
+    /// Wait for the next exit of the container.
+    NextExit,
+    /// Wait until the container is removed.
+    Removed,
+}
+
+impl AsRef<str> for WaitCondition {
+    fn as_ref(&self) -> &str {
+        match &self {
+            WaitCondition::NotRunning => "not-running",
+            WaitCondition::NextExit => "next-exit",
+            WaitCondition::Removed => "removed",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// Signal sent to a container with [`Container::kill_with`](crate::api::Container::kill_with).
+pub enum Signal {
+    Hup,
+    Int,
+    Quit,
+    Abrt,
+    #[default]
+    Kill,
+    Usr1,
+    Usr2,
+    Alrm,
+    Term,
+    Cont,
+    Stop,
+    Tstp,
+    Winch,
+    /// Any signal by its number, for example `34` for `SIGRTMIN` on Linux.
+    Number(u8),
+}
+
+impl std::fmt::Display for Signal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Signal::Hup => "SIGHUP",
+            Signal::Int => "SIGINT",
+            Signal::Quit => "SIGQUIT",
+            Signal::Abrt => "SIGABRT",
+            Signal::Kill => "SIGKILL",
+            Signal::Usr1 => "SIGUSR1",
+            Signal::Usr2 => "SIGUSR2",
+            Signal::Alrm => "SIGALRM",
+            Signal::Term => "SIGTERM",
+            Signal::Cont => "SIGCONT",
+            Signal::Stop => "SIGSTOP",
+            Signal::Tstp => "SIGTSTP",
+            Signal::Winch => "SIGWINCH",
+            Signal::Number(number) => return write!(f, "{}", number),
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Signal {
+    type Err = Error;
+
+    /// Parses a signal name like `SIGTERM` or `term`, or a signal number between 1 and 64.
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().to_ascii_uppercase();
+        let signal = match name.strip_prefix("SIG").unwrap_or(&name) {
+            "HUP" => Signal::Hup,
+            "INT" => Signal::Int,
+            "QUIT" => Signal::Quit,
+            "ABRT" => Signal::Abrt,
+            "KILL" => Signal::Kill,
+            "USR1" => Signal::Usr1,
+            "USR2" => Signal::Usr2,
+            "ALRM" => Signal::Alrm,
+            "TERM" => Signal::Term,
+            "CONT" => Signal::Cont,
+            "STOP" => Signal::Stop,
+            "TSTP" => Signal::Tstp,
+            "WINCH" => Signal::Winch,
+            number => match number.parse::<u8>() {
+                Ok(number @ 1..=64) => Signal::Number(number),
+                _ => return Err(Error::InvalidSignal(s.to_owned())),
+            },
+        };
+        Ok(signal)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "Name")]
+/// The behavior to apply when a container exits.
+pub enum RestartPolicy {
+    /// Never restart the container.
+    #[serde(rename = "no")]
+    No,
+    /// Always restart the container.
+    #[serde(rename = "always")]
+    Always,
+    /// Restart the container unless it was stopped manually.
+    #[serde(rename = "unless-stopped")]
+    UnlessStopped,
+    /// Restart the container only if it exits with a non-zero exit code, at most
+    /// `max_retry_count` times. A count of `0` retries indefinitely.
+    #[serde(rename = "on-failure")]
+    OnFailure {
+        #[serde(rename = "MaximumRetryCount")]
+        max_retry_count: u32,
+    },
+}
+
 /// Filter Opts for container listings
 pub enum ContainerFilter {
     Ancestor(ImageName),
@@ -121,42 +238,101 @@ pub enum ContainerFilter {
     Volume(String),
 }
 
-impl Filter for ContainerFilter {
-    fn query_item(&self) -> FilterItem {
+impl ContainerFilter {
+    fn key_val(&self) -> (&'static str, String) {
         use ContainerFilter::*;
         match &self {
-            Ancestor(name) => FilterItem::new("ancestor", name.to_string()),
-            Before(before) => FilterItem::new("before", before.to_owned()),
-            ExitCode(c) => FilterItem::new("exit", c.to_string()),
-            Health(health) => FilterItem::new("health", health.as_ref().to_string()),
-            Id(id) => FilterItem::new("id", id.to_owned()),
-            Isolation(isolation) => FilterItem::new("isolation", isolation.as_ref().to_string()),
-            IsTask(is_task) => FilterItem::new("is-task", is_task.to_string()),
-            LabelKey(key) => FilterItem::new("label", key.to_owned()),
-            Label(key, val) => FilterItem::new("label", format!("{}={}", key, val)),
-            Name(name) => FilterItem::new("name", name.to_owned()),
-            Publish(port) => FilterItem::new("publsh", port.to_string()),
-            Network(net) => FilterItem::new("net", net.to_owned()),
-            Since(since) => FilterItem::new("since", since.to_owned()),
-            Status(s) => FilterItem::new("status", s.as_ref().to_string()),
-            Volume(vol) => FilterItem::new("volume", vol.to_owned()),
+            Ancestor(name) => ("ancestor", name.to_string()),
+            Before(before) => ("before", before.to_owned()),
+            ExitCode(c) => ("exited", c.to_string()),
+            Health(health) => ("health", health.as_ref().to_string()),
+            Id(id) => ("id", id.to_owned()),
+            Isolation(isolation) => ("isolation", isolation.as_ref().to_string()),
+            IsTask(is_task) => ("is-task", is_task.to_string()),
+            LabelKey(key) => ("label", key.to_owned()),
+            Label(key, val) => ("label", format!("{}={}", key, val)),
+            Name(name) => ("name", name.to_owned()),
+            Publish(port) => ("publish", port.to_string()),
+            Network(net) => ("network", net.to_owned()),
+            Since(since) => ("since", since.to_owned()),
+            Status(s) => ("status", s.as_ref().to_string()),
+            Volume(vol) => ("volume", vol.to_owned()),
         }
     }
 }
 
+impl Filter for ContainerFilter {
+    fn query_item(&self) -> FilterItem {
+        let (key, val) = self.key_val();
+        FilterItem::new(key, val)
+    }
+}
+
 impl_opts_builder!(url => ContainerList);
 
 impl ContainerListOptsBuilder {
-    impl_filter_func!(
-        /// Filter the list of containers by one of the enum variants.
-        ContainerFilter
-    );
+    /// Filter the list of containers by one of the enum variants. Filters from multiple calls
+    /// and from the filter helpers like [`status`](ContainerListOptsBuilder::status) are combined.
+    pub fn filter(self, filters: impl IntoIterator<Item = ContainerFilter>) -> Self {
+        filters.into_iter().fold(self, |builder, filter| {
+            let (key, val) = filter.key_val();
+            builder.add_filter(key, val)
+        })
+    }
+
+    fn add_filter(mut self, key: &'static str, val: impl Into<String>) -> Self {
+        let mut filters: Filters = self
+            .params
+            .get("filters")
+            .and_then(|filters| serde_json::from_str(filters).ok())
+            .unwrap_or_default();
+        filters.add(key, val);
+        self.params.insert("filters", filters.to_json());
+        self
+    }
+
+    /// Only return containers with this status, one of `created`, `restarting`, `running`,
+    /// `removing`, `paused`, `exited` or `dead`. See [`ContainerStatus`](ContainerStatus).
+    pub fn status(self, status: impl AsRef<str>) -> Self {
+        self.add_filter("status", status.as_ref())
+    }
+
+    /// Only return containers with this health status, one of `starting`, `healthy`,
+    /// `unhealthy` or `none`. See [`Health`](Health).
+    pub fn health(self, health: impl AsRef<str>) -> Self {
+        self.add_filter("health", health.as_ref())
+    }
+
+    /// Only return containers created from this image or a descendant of it.
+    pub fn ancestor(self, image: impl Into<String>) -> Self {
+        self.add_filter("ancestor", image)
+    }
+
+    /// Only return containers connected to this network, by ID or name.
+    pub fn network(self, network: impl Into<String>) -> Self {
+        self.add_filter("network", network)
+    }
+
+    /// Only return containers using this volume, by name or mount point destination.
+    pub fn volume(self, volume: impl Into<String>) -> Self {
+        self.add_filter("volume", volume)
+    }
+
+    /// Only return containers with this label, either in the form of `key` or `key=value`.
+    pub fn label(self, label: impl Into<String>) -> Self {
+        self.add_filter("label", label)
+    }
 
     impl_url_bool_field!(
         /// If set to true all containers will be returned
         all => "all"
     );
 
+    impl_url_field!(
+        /// Return this number of most recently created containers, including non-running ones.
+        limit: usize => "limit"
+    );
+
     impl_url_str_field!(since => "since");
 
     impl_url_str_field!(before => "before");
@@ -171,6 +347,7 @@ impl ContainerListOptsBuilder {
 #[derive(Serialize, Debug, Clone)]
 pub struct ContainerCreateOpts {
     name: Option<String>,
+    platform: Option<String>,
     params: HashMap<&'static str, Value>,
 }
 
@@ -235,15 +412,65 @@ impl ContainerCreateOpts {
     pub(crate) fn name(&self) -> Option<&str> {
         self.name.as_deref()
     }
+
+    pub(crate) fn platform(&self) -> Option<&str> {
+        self.platform.as_deref()
+    }
+
+    pub(crate) fn image(&self) -> Option<&str> {
+        self.params.get("Image").and_then(Value::as_str)
+    }
+
+    /// Rejects combinations of options that conflict with each other.
+    fn validate(&self) -> Result<()> {
+        let network_mode = self
+            .params
+            .get("HostConfig.NetworkMode")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let is_set = |key: &str| match self.params.get(key) {
+            Some(Value::Bool(value)) => *value,
+            Some(Value::Array(values)) => !values.is_empty(),
+            Some(Value::Object(values)) => !values.is_empty(),
+            _ => false,
+        };
+
+        let shared_network = network_mode == "host" || network_mode.starts_with("container:");
+        if shared_network
+            && (is_set("HostConfig.PortBindings") || is_set("HostConfig.PublishAllPorts"))
+        {
+            return Err(Error::InvalidContainerOpts(format!(
+                "ports can't be published with the `{}` network mode",
+                network_mode
+            )));
+        }
+        if network_mode.starts_with("container:") {
+            for (key, option) in [
+                ("HostConfig.Links", "links"),
+                ("HostConfig.ExtraHosts", "extra hosts"),
+            ] {
+                if is_set(key) {
+                    return Err(Error::InvalidContainerOpts(format!(
+                        "{} can't be set with the `{}` network mode",
+                        option, network_mode
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Default)]
 pub struct ContainerCreateOptsBuilder {
     name: Option<String>,
+    platform: Option<String>,
     params: HashMap<&'static str, Value>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 /// Network protocol on which a port can be exposed.
 pub enum Protocol {
     Tcp,
@@ -261,11 +488,22 @@ impl AsRef<str> for Protocol {
     }
 }
 
+impl std::fmt::Display for Protocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
 impl FromStr for Protocol {
     type Err = Error;
 
+    /// Parses a protocol like `tcp` or the protocol suffix of a port like `80/tcp` or `/tcp`.
     fn from_str(s: &str) -> Result<Self> {
-        match s {
+        let protocol = s
+            .rsplit_once('/')
+            .map(|(_, protocol)| protocol)
+            .unwrap_or(s);
+        match protocol {
             "tcp" => Ok(Protocol::Tcp),
             "udp" => Ok(Protocol::Udp),
             "sctp" => Ok(Protocol::Sctp),
@@ -332,7 +570,7 @@ impl FromStr for PublishPort {
 
 impl ToString for PublishPort {
     fn to_string(&self) -> String {
-        format!("{}/{}", self.port, self.protocol.as_ref())
+        format!("{}/{}", self.port, self.protocol)
     }
 }
 
@@ -341,6 +579,7 @@ impl ContainerCreateOptsBuilder {
         Self {
             params: Default::default(),
             name: Some(name.into()),
+            platform: None,
         }
     }
 
@@ -353,6 +592,16 @@ impl ContainerCreateOptsBuilder {
         self
     }
 
+    /// Platform of the image in the format `os/arch[/variant]`, for example `linux/arm64`.
+    /// The platform is validated when the container is created.
+    pub fn platform<P>(mut self, platform: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.platform = Some(platform.into());
+        self
+    }
+
     /// enable all exposed ports on the container to be mapped to random, available, ports on the host
     pub fn publish_all_ports(mut self) -> Self {
         self.params
@@ -360,6 +609,7 @@ impl ContainerCreateOptsBuilder {
         self
     }
 
+    /// Expose a port in the container and bind it to `hostport` on the host.
     pub fn expose(mut self, srcport: PublishPort, hostport: u32) -> Self {
         let mut exposedport: HashMap<String, String> = HashMap::new();
         exposedport.insert("HostPort".to_string(), hostport.to_string());
@@ -382,16 +632,9 @@ impl ContainerCreateOptsBuilder {
         self.params
             .insert("HostConfig.PortBindings", json!(port_bindings));
 
-        // Replicate the port bindings over to the exposed ports config
-        let mut exposed_ports: HashMap<String, Value> = HashMap::new();
-        let empty_config: HashMap<String, Value> = HashMap::new();
-        for key in port_bindings.keys() {
-            exposed_ports.insert(key.to_string(), json!(empty_config));
-        }
-
-        self.params.insert("ExposedPorts", json!(exposed_ports));
-
-        self
+        // Replicate the port bindings over to the exposed ports config keeping the ports that
+        // were already exposed with `publish`
+        self.publish(srcport)
     }
 
     /// Publish a port in the container without assigning a port on the host
@@ -474,6 +717,8 @@ impl ContainerCreateOptsBuilder {
 
     impl_map_field!(json labels => "Labels");
 
+    impl_add_label!(ContainerCreateOptsBuilder);
+
     /// Whether to attach to `stdin`.
     pub fn attach_stdin(mut self, attach: bool) -> Self {
         self.params.insert("AttachStdin", json!(attach));
@@ -499,6 +744,24 @@ impl ContainerCreateOptsBuilder {
 
     impl_str_field!(network_mode => "HostConfig.NetworkMode");
 
+    /// Networks to connect the container to when creating it, keyed by network name or ID. Unlike
+    /// connecting the container with [`Network::connect`](crate::Network::connect) after creating
+    /// it, the container never exists without these networks. Docker hosts with an API version
+    /// below 1.44 accept only a single network here.
+    pub fn networks<N, I>(mut self, networks: I) -> Self
+    where
+        I: IntoIterator<Item = (N, EndpointConfig)>,
+        N: Into<String>,
+    {
+        let endpoints: Map<String, Value> = networks
+            .into_iter()
+            .map(|(network, config)| (network.into(), config.to_json()))
+            .collect();
+        self.params
+            .insert("NetworkingConfig.EndpointsConfig", Value::Object(endpoints));
+        self
+    }
+
     impl_vec_field!(env => "Env");
 
     impl_vec_field!(command => "Cmd");
@@ -514,15 +777,10 @@ impl ContainerCreateOptsBuilder {
 
     impl_str_field!(log_driver => "HostConfig.LogConfig.Type");
 
-    pub fn restart_policy(mut self, name: &str, maximum_retry_count: u64) -> Self {
+    /// The behavior to apply when the container exits.
+    pub fn restart_policy(mut self, policy: RestartPolicy) -> Self {
         self.params
-            .insert("HostConfig.RestartPolicy.Name", json!(name));
-        if name == "on-failure" {
-            self.params.insert(
-                "HostConfig.RestartPolicy.MaximumRetryCount",
-                json!(maximum_retry_count),
-            );
-        }
+            .insert("HostConfig.RestartPolicy", json!(policy));
         self
     }
 
@@ -546,11 +804,17 @@ impl ContainerCreateOptsBuilder {
 
     impl_str_field!(user => "User");
 
-    pub fn build(&self) -> ContainerCreateOpts {
-        ContainerCreateOpts {
+    /// Finish building ContainerCreateOpts. Returns
+    /// [`Error::InvalidContainerOpts`](crate::Error::InvalidContainerOpts) if the options
+    /// conflict with each other, like published ports together with the `host` network mode.
+    pub fn build(&self) -> Result<ContainerCreateOpts> {
+        let opts = ContainerCreateOpts {
             name: self.name.clone(),
+            platform: self.platform.clone(),
             params: self.params.clone(),
-        }
+        };
+        opts.validate()?;
+        Ok(opts)
     }
 }
 
@@ -573,6 +837,111 @@ impl ContainerRemoveOptsBuilder {
     );
 }
 
+impl_opts_builder!(json => ContainerUpdate);
+
+impl ContainerUpdateOptsBuilder {
+    impl_field!(
+        /// Memory limit in bytes.
+        memory: u64 => "Memory"
+    );
+
+    impl_field!(
+        /// Total memory limit (memory + swap) in bytes. Set as -1 to enable unlimited swap.
+        memory_swap: i64 => "MemorySwap"
+    );
+
+    impl_field!(
+        /// An integer value representing this container's relative CPU weight versus other
+        /// containers.
+        cpu_shares: u64 => "CpuShares"
+    );
+
+    impl_field!(
+        /// Microseconds of CPU time that the container can get in a CPU period.
+        cpu_quota: i64 => "CpuQuota"
+    );
+
+    impl_field!(
+        /// The length of a CPU period in microseconds.
+        cpu_period: u64 => "CpuPeriod"
+    );
+
+    impl_str_field!(
+        /// CPUs in which to allow execution (e.g., `0-3`, `0,1`).
+        cpuset_cpus => "CpusetCpus"
+    );
+
+    impl_field!(
+        /// Block IO weight (relative weight), accepts a value between 10 and 1000.
+        blkio_weight: u16 => "BlkioWeight"
+    );
+
+    /// The behavior to apply when the container exits.
+    pub fn restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.params.insert("RestartPolicy", json!(policy));
+        self
+    }
+}
+
+impl_opts_builder!(url => ContainerResize);
+
+impl ContainerResizeOptsBuilder {
+    impl_url_field!(
+        /// Height of the TTY session in characters.
+        height: u16 => "h"
+    );
+    impl_url_field!(
+        /// Width of the TTY session in characters.
+        width: u16 => "w"
+    );
+}
+
+impl_output_opts_builder!(ContainerAttach);
+
+impl ContainerAttachOptsBuilder {
+    impl_url_bool_field!(
+        /// Replay the logs of the container before streaming new output.
+        logs => "logs"
+    );
+
+    impl_url_bool_field!(
+        /// Stream the output of the container.
+        stream => "stream"
+    );
+
+    impl_url_bool_field!(
+        /// Attach to `stdin`.
+        stdin => "stdin"
+    );
+
+    impl_url_bool_field!(
+        /// Attach to `stdout`.
+        stdout => "stdout"
+    );
+
+    impl_url_bool_field!(
+        /// Attach to `stderr`.
+        stderr => "stderr"
+    );
+
+    impl_url_str_field!(
+        /// Override the key sequence for detaching a container, `ctrl-p,ctrl-q` by default. The
+        /// sequence is a comma separated list of keys, each being a single character or
+        /// `ctrl-<value>` where `<value>` is one of: `a-z`, `@`, `[`, `\`, `]`, `^` or `_`.
+        /// The format is validated when attaching.
+        detach_keys => "detachKeys"
+    );
+}
+
+impl ContainerAttachOpts {
+    pub(crate) fn validate(&self) -> Result<()> {
+        match self.params.get("detachKeys") {
+            Some(keys) => crate::opts::validate_detach_keys(keys),
+            None => Ok(()),
+        }
+    }
+}
+
 impl_opts_builder!(url => ContainerPrune);
 
 pub enum ContainerPruneFilter {
@@ -584,6 +953,8 @@ pub enum ContainerPruneFilter {
     #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     /// Prune containers created before this timestamp. Same as `Until` but takes a datetime object.
     UntilDate(chrono::DateTime<chrono::Utc>),
+    /// Prune containers created before this timestamp. Same as `Until` but takes a [`Timestamp`].
+    UntilTimestamp(Timestamp),
     /// Label in the form of `label=key`.
     LabelKey(String),
     /// Label in the form of `label=key=val`.
@@ -597,6 +968,7 @@ impl Filter for ContainerPruneFilter {
             Until(until) => FilterItem::new("until", until.to_owned()),
             #[cfg(feature = "chrono")]
             UntilDate(until) => FilterItem::new("until", until.timestamp().to_string()),
+            UntilTimestamp(until) => FilterItem::new("until", until.to_string()),
             LabelKey(label) => FilterItem::new("label", label.to_owned()),
             Label(key, val) => FilterItem::new("label", format!("{}={}", key, val)),
         }
@@ -640,7 +1012,8 @@ impl ContainerCommitOptsBuilder {
         pause => "pause"
     );
     impl_url_str_field!(
-        /// Dockerfile instructions to apply while committing
+        /// Dockerfile instructions to apply while committing, multiple instructions are separated
+        /// by a newline (e.g. `"ENV DEBUG=true\nEXPOSE 8080"`)
         changes => "changes"
     );
 }
@@ -648,15 +1021,37 @@ impl ContainerCommitOptsBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::opts::EndpointIpamConfig;
 
     macro_rules! test_case {
         ($opts:expr, $want:expr) => {
-            let opts = $opts.build();
+            let opts = $opts.build().unwrap();
 
             pretty_assertions::assert_eq!($want, opts.serialize().unwrap())
         };
     }
 
+    #[test]
+    fn container_update_opts() {
+        let opts = ContainerUpdateOptsBuilder::default()
+            .memory(256 * 1024 * 1024)
+            .build();
+        pretty_assertions::assert_eq!(r#"{"Memory":268435456}"#, opts.serialize().unwrap());
+
+        let opts = ContainerUpdateOpts::builder()
+            .cpuset_cpus("0,1")
+            .restart_policy(RestartPolicy::OnFailure { max_retry_count: 3 })
+            .build();
+        let body: Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "CpusetCpus": "0,1",
+                "RestartPolicy": {"Name": "on-failure", "MaximumRetryCount": 3}
+            })
+        );
+    }
+
     #[test]
     fn create_container_opts() {
         test_case!(
@@ -725,6 +1120,14 @@ mod tests {
             r#"{"ExposedPorts":{"1337/tcp":{},"6969/sctp":{},"80/udp":{}},"HostConfig":{},"Image":"test_image"}"#
         );
 
+        test_case!(
+            ContainerCreateOptsBuilder::default()
+                .image("nginx")
+                .publish(PublishPort::tcp(443))
+                .expose(PublishPort::tcp(80), 8080),
+            r#"{"ExposedPorts":{"443/tcp":{},"80/tcp":{}},"HostConfig":{"PortBindings":{"80/tcp":[{"HostPort":"8080"}]}},"Image":"nginx"}"#
+        );
+
         test_case!(
             ContainerCreateOptsBuilder::default()
                 .image("test_image")
@@ -742,15 +1145,276 @@ mod tests {
         test_case!(
             ContainerCreateOptsBuilder::default()
                 .image("test_image")
-                .restart_policy("on-failure", 10),
+                .restart_policy(RestartPolicy::OnFailure {
+                    max_retry_count: 10
+                }),
             r#"{"HostConfig":{"RestartPolicy":{"MaximumRetryCount":10,"Name":"on-failure"}},"Image":"test_image"}"#
         );
 
         test_case!(
             ContainerCreateOptsBuilder::default()
                 .image("test_image")
-                .restart_policy("always", 0),
+                .restart_policy(RestartPolicy::Always),
             r#"{"HostConfig":{"RestartPolicy":{"Name":"always"}},"Image":"test_image"}"#
         );
     }
+
+    #[test]
+    fn restart_policy_serialization() {
+        assert_eq!(
+            serde_json::to_value(RestartPolicy::OnFailure { max_retry_count: 5 }).unwrap(),
+            json!({"Name": "on-failure", "MaximumRetryCount": 5})
+        );
+        assert_eq!(
+            serde_json::to_value(RestartPolicy::Always).unwrap(),
+            json!({"Name": "always"})
+        );
+        assert_eq!(
+            serde_json::to_value(RestartPolicy::UnlessStopped).unwrap(),
+            json!({"Name": "unless-stopped"})
+        );
+        assert_eq!(
+            serde_json::to_value(RestartPolicy::No).unwrap(),
+            json!({"Name": "no"})
+        );
+    }
+
+    #[test]
+    fn container_create_networks() {
+        let opts = ContainerCreateOpts::builder()
+            .image("nginx")
+            .networks(HashMap::from([(
+                "backend".to_string(),
+                EndpointConfig::new()
+                    .aliases(["web"])
+                    .ipam_config(EndpointIpamConfig::new().ipv4("172.20.0.5")),
+            )]))
+            .build()
+            .unwrap();
+
+        let body: Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(
+            body["NetworkingConfig"],
+            json!({
+                "EndpointsConfig": {
+                    "backend": {
+                        "Aliases": ["web"],
+                        "IPAMConfig": {"IPv4Address": "172.20.0.5"}
+                    }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn protocol_parse_and_serialize() {
+        assert_eq!("/sctp".parse::<Protocol>().unwrap(), Protocol::Sctp);
+        assert_eq!("80/sctp".parse::<Protocol>().unwrap(), Protocol::Sctp);
+        assert_eq!("udp".parse::<Protocol>().unwrap(), Protocol::Udp);
+        assert!(matches!(
+            "80/icmp".parse::<Protocol>(),
+            Err(Error::InvalidProtocol(protocol)) if protocol == "icmp"
+        ));
+
+        assert_eq!(Protocol::Udp.to_string(), "udp");
+        assert_eq!(serde_json::to_value(Protocol::Udp).unwrap(), json!("udp"));
+        assert_eq!(
+            serde_json::from_value::<Protocol>(json!("tcp")).unwrap(),
+            Protocol::Tcp
+        );
+        assert_eq!(PublishPort::sctp(5000).to_string(), "5000/sctp");
+    }
+
+    #[test]
+    fn signal_parse_and_display() {
+        assert_eq!(Signal::Term.to_string(), "SIGTERM");
+        assert_eq!(Signal::Number(34).to_string(), "34");
+        assert_eq!(Signal::default(), Signal::Kill);
+
+        assert_eq!("SIGTERM".parse::<Signal>().unwrap(), Signal::Term);
+        assert_eq!("term".parse::<Signal>().unwrap(), Signal::Term);
+        assert_eq!("SIGwinch".parse::<Signal>().unwrap(), Signal::Winch);
+        assert_eq!("9".parse::<Signal>().unwrap(), Signal::Number(9));
+        for invalid in ["SIGFOO", "", "0", "65", "SIG"] {
+            assert!(matches!(
+                invalid.parse::<Signal>(),
+                Err(Error::InvalidSignal(signal)) if signal == invalid
+            ));
+        }
+    }
+
+    #[test]
+    fn container_list_opts() {
+        let opts = ContainerListOpts::builder()
+            .all(true)
+            .limit(5)
+            .sized(true)
+            .build();
+
+        let serialized = opts.serialize().unwrap();
+        assert!(serialized.contains("all=true"));
+        assert!(serialized.contains("limit=5"));
+        assert!(serialized.contains("size=true"));
+
+        assert!(ContainerListOpts::builder().build().serialize().is_none());
+    }
+
+    #[test]
+    fn container_list_filters() {
+        let opts = ContainerListOpts::builder()
+            .all(true)
+            .status("running")
+            .label("env=prod")
+            .build();
+
+        let query = opts.serialize().unwrap();
+        let pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs["all"], "true");
+        assert_eq!(
+            pairs["filters"],
+            r#"{"label":["env=prod"],"status":["running"]}"#
+        );
+
+        let opts = ContainerListOpts::builder()
+            .filter([ContainerFilter::Status(ContainerStatus::Exited)])
+            .health(Health::Unhealthy)
+            .network("backend")
+            .ancestor("nginx")
+            .volume("/data")
+            .status(ContainerStatus::Dead)
+            .build();
+
+        let query = opts.serialize().unwrap();
+        let (key, filters) = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .next()
+            .unwrap();
+        assert_eq!(key, "filters");
+        let filters: HashMap<String, Vec<String>> = serde_json::from_str(&filters).unwrap();
+        assert_eq!(
+            filters,
+            HashMap::from([
+                (
+                    "status".to_string(),
+                    vec!["exited".to_string(), "dead".to_string()]
+                ),
+                ("health".to_string(), vec!["unhealthy".to_string()]),
+                ("network".to_string(), vec!["backend".to_string()]),
+                ("ancestor".to_string(), vec!["nginx".to_string()]),
+                ("volume".to_string(), vec!["/data".to_string()]),
+            ])
+        );
+    }
+
+    #[test]
+    fn container_attach_opts() {
+        let opts = ContainerAttachOpts::builder()
+            .stdin(true)
+            .logs(false)
+            .detach_keys("ctrl-x")
+            .build();
+
+        let query = opts.serialize().unwrap();
+        let pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        assert_eq!(
+            pairs,
+            HashMap::from([
+                ("stdin".to_string(), "true".to_string()),
+                ("logs".to_string(), "false".to_string()),
+                ("detachKeys".to_string(), "ctrl-x".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn container_commit_opts() {
+        let opts = ContainerCommitOpts::builder()
+            .repo("test-repo")
+            .tag("v1")
+            .changes("ENV DEBUG=true\nEXPOSE 8080")
+            .build()
+            .with_container("test-container");
+
+        let query = opts.serialize().unwrap();
+        let pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        assert_eq!(
+            pairs,
+            HashMap::from([
+                ("container".to_string(), "test-container".to_string()),
+                ("repo".to_string(), "test-repo".to_string()),
+                ("tag".to_string(), "v1".to_string()),
+                (
+                    "changes".to_string(),
+                    "ENV DEBUG=true\nEXPOSE 8080".to_string()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn container_prune_filters() {
+        let opts = ContainerPruneOpts::builder()
+            .filter([
+                ContainerPruneFilter::Until("1h".into()),
+                ContainerPruneFilter::Label("app".into(), "test".into()),
+            ])
+            .build();
+
+        let query = opts.serialize().unwrap();
+        let (key, filters) = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .next()
+            .unwrap();
+        assert_eq!(key, "filters");
+        let filters: HashMap<String, Vec<String>> = serde_json::from_str(&filters).unwrap();
+        assert_eq!(
+            filters,
+            HashMap::from([
+                ("until".to_string(), vec!["1h".to_string()]),
+                ("label".to_string(), vec!["app=test".to_string()]),
+            ])
+        );
+    }
+
+    #[test]
+    fn validate_create_opts() {
+        let opts = ContainerCreateOpts::builder()
+            .image("nginx")
+            .network_mode("host")
+            .expose(PublishPort::tcp(80), 8080)
+            .build();
+        match opts {
+            Err(Error::InvalidContainerOpts(msg)) => assert!(msg.contains("`host`")),
+            res => panic!("Expected Error::InvalidContainerOpts, got {:?}", res),
+        }
+
+        let opts = ContainerCreateOpts::builder()
+            .network_mode("container:web")
+            .extra_hosts(["db:10.0.0.2"])
+            .build();
+        assert!(matches!(opts, Err(Error::InvalidContainerOpts(_))));
+
+        for opts in [
+            ContainerCreateOpts::builder().build(),
+            ContainerCreateOpts::builder()
+                .network_mode("host")
+                .tty(true)
+                .attach_stdin(true)
+                .build(),
+            ContainerCreateOpts::builder()
+                .network_mode("bridge")
+                .expose(PublishPort::tcp(80), 8080)
+                .publish_all_ports()
+                .build(),
+        ] {
+            assert!(opts.is_ok());
+        }
+    }
 }
@@ -1,4 +1,6 @@
-use containers_api::{impl_field, impl_opts_builder, impl_str_field, impl_vec_field};
+use containers_api::{
+    impl_field, impl_opts_builder, impl_str_field, impl_url_field, impl_vec_field,
+};
 
 impl_opts_builder!(json => ExecCreate);
 
@@ -13,6 +15,11 @@ impl ExecCreateOptsBuilder {
         env => "Env"
     );
 
+    impl_field!(
+        /// Attach to stdin of the exec command.
+        attach_stdin: bool => "AttachStdin"
+    );
+
     impl_field!(
         /// Attach to stdout of the exec command.
         attach_stdout: bool => "AttachStdout"
@@ -24,8 +31,10 @@ impl ExecCreateOptsBuilder {
     );
 
     impl_str_field!(
-        /// Override the key sequence for detaching a container. Format is a single
-        /// character [a-Z] or ctrl-<value> where <value> is one of: a-z, @, ^, [, , or _.
+        /// Override the key sequence for detaching from the exec session, `ctrl-p,ctrl-q` by
+        /// default. The sequence is a comma separated list of keys, each being a single character
+        /// or `ctrl-<value>` where `<value>` is one of: `a-z`, `@`, `[`, `\`, `]`, `^` or `_`.
+        /// The format is validated when the exec instance is created.
         detach_keys => "DetachKeys"
     );
 
@@ -51,9 +60,53 @@ impl ExecCreateOptsBuilder {
     );
 }
 
-impl_opts_builder!(json => ExecResize);
+impl ExecCreateOpts {
+    pub(crate) fn validate(&self) -> crate::Result<()> {
+        match self
+            .params
+            .get("DetachKeys")
+            .and_then(serde_json::Value::as_str)
+        {
+            Some(keys) => crate::opts::validate_detach_keys(keys),
+            None => Ok(()),
+        }
+    }
+}
+
+impl_opts_builder!(url => ExecResize);
 
 impl ExecResizeOptsBuilder {
-    impl_field!(height: u64 => "Height");
-    impl_field!(width: u64 => "Width");
+    impl_url_field!(
+        /// Height of the TTY session in characters.
+        height: u16 => "h"
+    );
+    impl_url_field!(
+        /// Width of the TTY session in characters.
+        width: u16 => "w"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exec_resize_opts() {
+        let opts = ExecResizeOpts::builder().width(120).height(40).build();
+        let query = opts.serialize().unwrap();
+
+        let mut pairs: Vec<_> = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        pairs.sort();
+        assert_eq!(
+            pairs,
+            vec![
+                ("h".to_string(), "40".to_string()),
+                ("w".to_string(), "120".to_string())
+            ]
+        );
+
+        assert!(ExecResizeOpts::builder().build().serialize().is_none());
+    }
 }
@@ -1,3 +1,4 @@
+use crate::{opts::Timestamp, Error, Result};
 use containers_api::opts::{Filter, FilterItem};
 use containers_api::url::encoded_pairs;
 use containers_api::{
@@ -49,7 +50,9 @@ impl RegistryAuth {
         RegistryAuthBuilder::default()
     }
 
-    /// serialize authentication as JSON in base64
+    /// serialize authentication as JSON in URL-safe base64 as expected by the `X-Registry-Auth`
+    /// header. The plain JSON form used by [`Docker::auth`](crate::Docker::auth) is available
+    /// through the `Serialize` implementation.
     pub fn serialize(&self) -> String {
         serde_json::to_string(self)
             .map(|c| base64::encode_config(&c, base64::URL_SAFE))
@@ -150,22 +153,38 @@ impl PullOpts {
     pub(crate) fn auth_header(&self) -> Option<String> {
         self.auth.clone().map(|a| a.serialize())
     }
+
+    pub(crate) fn platform(&self) -> Option<&str> {
+        self.params.get("platform").and_then(|p| p.as_str())
+    }
+
+    /// Rejects options that the daemon would refuse, so that the error is reported before any
+    /// request is sent.
+    pub(crate) fn validate(&self) -> Result<()> {
+        let param = |key| self.params.get(key).and_then(serde_json::Value::as_str);
+
+        if let Some(platform) = self.platform() {
+            crate::opts::validate_platform(platform)?;
+        }
+        if let (Some(image), Some(tag)) = (param("fromImage"), param("tag")) {
+            if image.contains('@') && !tag.is_empty() {
+                return Err(Error::InvalidPullOpts(format!(
+                    "the image `{}` is pinned to a digest so the tag `{}` can't be set",
+                    image, tag
+                )));
+            }
+        }
+
+        Ok(())
+    }
 }
 
+#[derive(Default)]
 pub struct PullOptsBuilder {
     auth: Option<RegistryAuth>,
     params: HashMap<&'static str, serde_json::Value>,
 }
 
-impl Default for PullOptsBuilder {
-    fn default() -> Self {
-        let mut params = HashMap::new();
-        params.insert("tag", serde_json::Value::String("latest".into()));
-
-        PullOptsBuilder { auth: None, params }
-    }
-}
-
 impl PullOptsBuilder {
     impl_str_field!(
     /// Name of the image to pull. The name may include a tag or digest.
@@ -173,6 +192,10 @@ impl PullOptsBuilder {
     /// If an untagged value is provided and no `tag` is provided, _all_
     /// tags will be pulled
     /// The pull is cancelled if the HTTP connection is closed.
+    ///
+    /// A name pinned to a digest like `alpine@sha256:...` can't be combined with a `tag`, the
+    /// pull fails with [`Error::InvalidPullOpts`](crate::Error::InvalidPullOpts) without sending
+    /// the request if both are set.
     image => "fromImage");
 
     impl_str_field!(src => "fromSrc");
@@ -181,8 +204,8 @@ impl PullOptsBuilder {
     /// Repository name given to an image when it is imported. The repo may include a tag.
     /// This parameter may only be used when importing an image.
     /// 
-    /// By default a `latest` tag is added when calling
-    /// [PullOptsBuilder::default](PullOptsBuilder::default).
+    /// By default a `latest` tag is added unless a tag is set or the image is pinned to a
+    /// digest.
     repo => "repo");
 
     impl_str_field!(
@@ -190,15 +213,31 @@ impl PullOptsBuilder {
     /// this causes all tags for the given image to be pulled.
     tag => "tag");
 
+    impl_str_field!(
+    /// Platform in the format `os/arch[/variant]`, for example `linux/arm64`. When pulling
+    /// an image that is already present for a different platform the image is pulled again.
+    platform => "platform");
+
     pub fn auth(mut self, auth: RegistryAuth) -> Self {
         self.auth = Some(auth);
         self
     }
 
     pub fn build(self) -> PullOpts {
+        let mut params = self.params;
+        let pinned = matches!(
+            params.get("fromImage").and_then(serde_json::Value::as_str),
+            Some(image) if image.contains('@')
+        );
+        if !pinned {
+            params
+                .entry("tag")
+                .or_insert_with(|| serde_json::Value::String("latest".into()));
+        }
+
         PullOpts {
             auth: self.auth,
-            params: self.params,
+            params,
         }
     }
 }
@@ -230,6 +269,26 @@ impl ImageBuildOpts {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Version of the builder backend used to build an image.
+pub enum BuilderVersion {
+    /// The classic builder.
+    Classic,
+    /// The [BuildKit](https://docs.docker.com/build/buildkit/) builder. Progress of the build is
+    /// reported as
+    /// [`ImageBuildChunk::BuildKitTrace`](crate::models::ImageBuildChunk::BuildKitTrace).
+    BuildKit,
+}
+
+impl AsRef<str> for BuilderVersion {
+    fn as_ref(&self) -> &str {
+        match &self {
+            BuilderVersion::Classic => "1",
+            BuilderVersion::BuildKit => "2",
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct ImageBuildOptsBuilder {
     path: PathBuf,
@@ -273,9 +332,15 @@ impl ImageBuildOptsBuilder {
 
     impl_url_bool_field!(
         /// Don't use the image cache when building image.
-        nocahe => "nocache"
+        nocache => "nocache"
     );
 
+    #[deprecated(note = "misspelled, use `nocache` instead")]
+    /// Don't use the image cache when building image.
+    pub fn nocahe(self, nocache: bool) -> Self {
+        self.nocache(nocache)
+    }
+
     impl_url_str_field!(
         /// Attempt to pull the image even if an older image exists locally.
         pull => "pull"
@@ -315,7 +380,10 @@ impl ImageBuildOptsBuilder {
         cpu_quota: usize => "cpuquota"
     );
 
-    // TODO: buildargs
+    impl_map_field!(url
+        /// Build-time variables available to the `ARG` instructions of the Dockerfile.
+        buildargs => "buildargs"
+    );
 
     impl_url_field!(
         /// Size of /dev/shm in bytes. The size must be greater than 0. If omitted the system uses 64MB.
@@ -348,6 +416,18 @@ impl ImageBuildOptsBuilder {
         outputs => "outputs"
     );
 
+    /// Version of the builder backend to use, defaults to the classic builder.
+    pub fn version(mut self, version: BuilderVersion) -> Self {
+        self.params.insert("version", version.as_ref().into());
+        self
+    }
+
+    impl_url_str_field!(
+        /// Session ID of a BuildKit session, required by BuildKit to access files of the client
+        /// outside of the build context.
+        session => "session"
+    );
+
     impl_map_field!(url
         /// Add labels to this image.
         labels => "labels"
@@ -426,6 +506,8 @@ pub enum ImageFilter {
     LabelKey(String),
     /// Label in the form of `label=key=val`.
     Label(String, String),
+    /// Image name or reference pattern, for example `alpine` or `alpine:3.*`.
+    Reference(String),
     Since(ImageName),
 }
 
@@ -437,6 +519,7 @@ impl Filter for ImageFilter {
             Dangling => FilterItem::new("dangling", true.to_string()),
             LabelKey(n) => FilterItem::new("label", n.to_owned()),
             Label(n, v) => FilterItem::new("label", format!("{}={}", n, v)),
+            Reference(r) => FilterItem::new("reference", r.to_owned()),
             Since(name) => FilterItem::new("since", name.to_string()),
         }
     }
@@ -459,6 +542,42 @@ impl ImageListOptsBuilder {
     );
 }
 
+/// Filter type used to filter image search results.
+pub enum ImageSearchFilter {
+    /// Only show results with at least this many stars.
+    Stars(u64),
+    IsOfficial(bool),
+    IsAutomated(bool),
+}
+
+impl Filter for ImageSearchFilter {
+    fn query_item(&self) -> FilterItem {
+        use ImageSearchFilter::*;
+        match &self {
+            Stars(stars) => FilterItem::new("stars", stars.to_string()),
+            IsOfficial(official) => FilterItem::new("is-official", official.to_string()),
+            IsAutomated(automated) => FilterItem::new("is-automated", automated.to_string()),
+        }
+    }
+}
+
+impl_opts_builder!(url => ImageSearch);
+
+impl ImageSearchOptsBuilder {
+    impl_url_str_field!(
+        /// Term to search for.
+        term => "term"
+    );
+    impl_url_field!(
+        /// Maximum number of search results.
+        limit: u64 => "limit"
+    );
+    impl_filter_func!(
+        /// Filter the search results by one of the variants of the enum.
+        ImageSearchFilter
+    );
+}
+
 impl_opts_builder!(url => ImageRemove);
 
 impl ImageRemoveOptsBuilder {
@@ -482,6 +601,8 @@ pub enum ImagesPruneFilter {
     #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     /// Prune images created before this timestamp. Same as `Until` but takes a datetime object.
     UntilDate(chrono::DateTime<chrono::Utc>),
+    /// Prune images created before this timestamp. Same as `Until` but takes a [`Timestamp`].
+    UntilTimestamp(Timestamp),
     /// Prune images created before this timestamp. The <timestamp> can be Unix timestamps,
     /// date formatted timestamps, or Go duration strings (e.g. 10m, 1h30m)
     /// computed relative to the daemon machine’s time.
@@ -500,6 +621,7 @@ impl Filter for ImagesPruneFilter {
             Until(until) => FilterItem::new("until", until.to_owned()),
             #[cfg(feature = "chrono")]
             UntilDate(until) => FilterItem::new("until", until.timestamp().to_string()),
+            UntilTimestamp(until) => FilterItem::new("until", until.to_string()),
             LabelKey(label) => FilterItem::new("label", label.to_owned()),
             Label(key, val) => FilterItem::new("label", format!("{}={}", key, val)),
         }
@@ -656,4 +778,211 @@ mod tests {
             opts.serialize()
         );
     }
+
+    /// Test that the header value is URL-safe base64 of the JSON object
+    #[test]
+    fn registry_auth_url_safe() {
+        let opts = RegistryAuth::builder()
+            .username("user_abc")
+            .password("~~~~~~")
+            .build();
+        let json = r#"{"username":"user_abc","password":"~~~~~~"}"#;
+        let encoded = opts.serialize();
+
+        assert!(base64::encode(json).contains('+'));
+        assert!(!encoded.contains('+') && !encoded.contains('/'));
+        assert_eq!(
+            base64::decode_config(&encoded, base64::URL_SAFE).unwrap(),
+            json.as_bytes()
+        );
+        assert_eq!(serde_json::to_string(&opts).unwrap(), json);
+    }
+
+    #[test]
+    fn image_push_opts() {
+        let opts = ImagePushOpts::builder()
+            .tag("v1")
+            .auth(
+                RegistryAuth::builder()
+                    .username("user_abc")
+                    .password("password_abc")
+                    .build(),
+            )
+            .build();
+        assert_eq!(opts.serialize(), Some("tag=v1".to_string()));
+        assert_eq!(
+            opts.auth_header(),
+            Some(base64::encode(
+                r#"{"username":"user_abc","password":"password_abc"}"#
+            ))
+        );
+
+        assert!(ImagePushOpts::builder().build().auth_header().is_none());
+    }
+
+    #[test]
+    fn image_remove_opts() {
+        let opts = ImageRemoveOpts::builder().force(true).build();
+        assert_eq!(opts.serialize(), Some("force=true".to_string()));
+
+        let opts = ImageRemoveOpts::builder().build();
+        assert_eq!(opts.serialize(), None);
+    }
+
+    #[test]
+    fn image_build_opts() {
+        let opts = ImageBuildOpts::builder("/tmp")
+            .tag("test:latest")
+            .buildargs([("VERSION", "1.0")])
+            .nocache(true)
+            .target("release")
+            .build();
+
+        let query = opts.serialize().unwrap();
+        let pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        assert_eq!(pairs.len(), 4);
+        assert_eq!(pairs["t"], "test:latest");
+        assert_eq!(pairs["nocache"], "true");
+        assert_eq!(pairs["target"], "release");
+        assert_eq!(
+            serde_json::from_str::<HashMap<String, String>>(&pairs["buildargs"]).unwrap(),
+            HashMap::from([("VERSION".to_string(), "1.0".to_string())])
+        );
+    }
+
+    #[test]
+    fn image_build_opts_version() {
+        let opts = ImageBuildOpts::builder("/tmp")
+            .version(BuilderVersion::BuildKit)
+            .build();
+        assert_eq!(opts.serialize(), Some("version=2".to_string()));
+
+        let opts = ImageBuildOpts::builder("/tmp")
+            .version(BuilderVersion::Classic)
+            .build();
+        assert_eq!(opts.serialize(), Some("version=1".to_string()));
+    }
+
+    #[test]
+    fn image_list_opts() {
+        let opts = ImageListOpts::builder()
+            .all(true)
+            .digests(true)
+            .filter([ImageFilter::Reference("alpine:3.*".into())])
+            .build();
+
+        let query = opts.serialize().unwrap();
+        let pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        assert_eq!(pairs.len(), 3);
+        assert_eq!(pairs["all"], "true");
+        assert_eq!(pairs["digests"], "true");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&pairs["filters"]).unwrap(),
+            serde_json::json!({"reference": ["alpine:3.*"]})
+        );
+    }
+
+    #[test]
+    fn image_search_opts() {
+        let opts = ImageSearchOpts::builder().term("nginx").limit(5).build();
+
+        let query = opts.serialize().unwrap();
+        let pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs["term"], "nginx");
+        assert_eq!(pairs["limit"], "5");
+
+        let opts = ImageSearchOpts::builder()
+            .term("nginx")
+            .filter([
+                ImageSearchFilter::IsOfficial(true),
+                ImageSearchFilter::Stars(3),
+            ])
+            .build();
+        let query = opts.serialize().unwrap();
+        let pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&pairs["filters"]).unwrap(),
+            serde_json::json!({"is-official": ["true"], "stars": ["3"]})
+        );
+    }
+
+    #[test]
+    fn image_prune_dangling_false() {
+        let opts = ImagePruneOpts::builder()
+            .filter([
+                ImagesPruneFilter::Dangling(false),
+                ImagesPruneFilter::Until("24h".into()),
+            ])
+            .build();
+
+        let query = opts.serialize().unwrap();
+        let (key, filters) = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .next()
+            .unwrap();
+        assert_eq!(key, "filters");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&filters).unwrap(),
+            serde_json::json!({"dangling": ["false"], "until": ["24h"]})
+        );
+    }
+
+    #[test]
+    fn pull_opts() {
+        let opts = PullOpts::builder()
+            .image("alpine")
+            .platform("linux/arm64")
+            .auth(RegistryAuth::token("abc"))
+            .build();
+
+        let mut pairs: Vec<_> = url::form_urlencoded::parse(opts.serialize().unwrap().as_bytes())
+            .into_owned()
+            .collect();
+        pairs.sort();
+        assert_eq!(
+            pairs,
+            vec![
+                ("fromImage".to_string(), "alpine".to_string()),
+                ("platform".to_string(), "linux/arm64".to_string()),
+                ("tag".to_string(), "latest".to_string()),
+            ]
+        );
+        assert_eq!(
+            opts.auth_header(),
+            Some(base64::encode(r#"{"identitytoken":"abc"}"#))
+        );
+    }
+
+    #[test]
+    fn pull_opts_digest() {
+        let image =
+            "alpine@sha256:8914eb54f968791faf6a8638949e480fef81e697984fba772b3976835194c6d4";
+        let opts = PullOpts::builder().image(image).build();
+        assert!(opts.validate().is_ok());
+        assert_eq!(
+            opts.serialize().unwrap(),
+            encoded_pairs([("fromImage", image)])
+        );
+
+        let opts = PullOpts::builder().image(image).tag("3.17").build();
+        match opts.validate() {
+            Err(Error::InvalidPullOpts(msg)) => assert!(msg.contains("`3.17`")),
+            res => panic!("Expected Error::InvalidPullOpts, got {:?}", res),
+        }
+
+        let opts = PullOpts::builder()
+            .image("alpine")
+            .tag("sha256:8914eb54")
+            .build();
+        assert!(opts.validate().is_ok());
+    }
 }
@@ -1,4 +1,171 @@
 //! Options used for configuring the behavior of certain API endpoints
+
+/// Implements a typed setter of a query parameter of a `url` options builder.
+///
+/// The value is stored with its `Display` representation, so a `bool` is sent as `true` or
+/// `false`. The `filter` form takes items of a [`Filter`](containers_api::opts::Filter) type and
+/// stores them as [`Filters`], combined with the filters of previous calls.
+macro_rules! impl_query_field {
+    ($(#[doc = $docs:expr])* filter: $filter_ty:ty => $param_name:literal) => {
+        $(
+            #[doc = $docs]
+        )*
+        pub fn filter(mut self, filters: impl IntoIterator<Item = $filter_ty>) -> Self {
+            use containers_api::opts::Filter;
+
+            let mut params = self
+                .params
+                .get($param_name)
+                .and_then(|json| serde_json::from_str::<crate::opts::Filters>(json).ok())
+                .unwrap_or_default();
+            for item in filters.into_iter().map(|filter| filter.query_item()) {
+                params.add(item.key(), item.to_string());
+            }
+            self.params.insert($param_name, params.to_json());
+            self
+        }
+    };
+    ($(#[doc = $docs:expr])* $name:ident: $ty:ty => $param_name:literal) => {
+        $(
+            #[doc = $docs]
+        )*
+        pub fn $name(mut self, $name: $ty) -> Self {
+            self.params.insert($param_name, $name.to_string());
+            self
+        }
+    };
+}
+
+/// Implements `add_label` on a builder that also sets all labels at once with `labels`. The
+/// builder's `params` have to implement [`LabelParams`].
+macro_rules! impl_add_label {
+    ($builder:ident) => {
+        #[doc = concat!(
+            "Add a single label. Unlike [`labels`](", stringify!($builder), "::labels) this keeps ",
+            "labels that were added before."
+        )]
+        pub fn add_label<K, V>(mut self, key: K, val: V) -> Self
+        where
+            K: Into<String>,
+            V: Into<String>,
+        {
+            if let Some(labels) = crate::opts::LabelParams::labels_mut(&mut self.params) {
+                crate::opts::insert_label(labels, key.into(), val.into());
+            }
+            self
+        }
+    };
+}
+
+/// Parameters of a builder containing the `Labels` JSON object, see `impl_add_label`.
+pub(crate) trait LabelParams {
+    /// Returns the labels, inserting an empty object if there are none yet. Returns `None` if
+    /// the labels failed to serialize.
+    fn labels_mut(&mut self) -> Option<&mut serde_json::Value>;
+}
+
+impl LabelParams for std::collections::HashMap<&'static str, serde_json::Value> {
+    fn labels_mut(&mut self) -> Option<&mut serde_json::Value> {
+        Some(
+            self.entry("Labels")
+                .or_insert_with(|| serde_json::json!({})),
+        )
+    }
+}
+
+// Params of builders generated with `impl_opts_builder!(json => ...)`, like
+// `VolumeCreateOptsBuilder`.
+impl LabelParams for std::collections::BTreeMap<&'static str, serde_json::Value> {
+    fn labels_mut(&mut self) -> Option<&mut serde_json::Value> {
+        Some(
+            self.entry("Labels")
+                .or_insert_with(|| serde_json::json!({})),
+        )
+    }
+}
+
+impl LabelParams for std::collections::HashMap<&'static str, crate::Result<serde_json::Value>> {
+    fn labels_mut(&mut self) -> Option<&mut serde_json::Value> {
+        self.entry("Labels")
+            .or_insert_with(|| Ok(serde_json::json!({})))
+            .as_mut()
+            .ok()
+    }
+}
+
+/// Inserts a single label into the JSON object `labels`, keeping labels inserted before. Used by
+/// the `add_label` methods of builders that also set all labels at once with `labels`.
+pub(crate) fn insert_label(labels: &mut serde_json::Value, key: String, val: String) {
+    if !labels.is_object() {
+        *labels = serde_json::json!({});
+    }
+    if let Some(labels) = labels.as_object_mut() {
+        labels.insert(key, serde_json::Value::String(val));
+    }
+}
+
+/// Same as `impl_opts_builder!(url => ...)` for options of endpoints returning the output of a
+/// container. The options additionally carry whether the container was created with a TTY, which
+/// decides how the output is decoded and is not sent to the daemon.
+macro_rules! impl_output_opts_builder {
+    ($(#[doc = $docs:expr])* $name:ident) => {
+        paste::item! {
+            $(
+                #[doc = $docs]
+            )*
+            #[derive(serde::Serialize, Debug, Default, Clone)]
+            pub struct [< $name Opts >] {
+                params: std::collections::BTreeMap<&'static str, String>,
+                #[serde(skip)]
+                tty: Option<bool>,
+            }
+
+            impl [< $name Opts >] {
+                #[doc = concat!("Returns a new instance of a builder for ", stringify!($name), "Opts.")]
+                pub fn builder() -> [< $name OptsBuilder >] {
+                    [< $name OptsBuilder >]::default()
+                }
+
+                /// Serialize options as a URL query String. Returns None if no options are defined.
+                pub fn serialize(&self) -> Option<String> {
+                    let params = containers_api::url::encoded_pairs(&self.params);
+                    (!params.is_empty()).then_some(params)
+                }
+
+                /// Whether the container was created with a TTY, `None` if it is unknown.
+                pub(crate) fn tty(&self) -> Option<bool> {
+                    self.tty
+                }
+            }
+
+            #[doc = concat!("A builder struct for ", stringify!($name), "Opts.")]
+            #[derive(Default, Debug, Clone)]
+            pub struct [< $name OptsBuilder >] {
+                params: std::collections::BTreeMap<&'static str, String>,
+                tty: Option<bool>,
+            }
+
+            impl [< $name OptsBuilder >] {
+                /// Whether the container was created with a TTY, which decides if its output is
+                /// decoded as a raw or a multiplexed stream. If this isn't set the container is
+                /// inspected first to find out.
+                pub fn tty(mut self, tty: bool) -> Self {
+                    self.tty = Some(tty);
+                    self
+                }
+
+                #[doc = concat!("Finish building ", stringify!($name), "Opts.")]
+                pub fn build(self) -> [< $name Opts >] {
+                    [< $name Opts >] {
+                        params: self.params,
+                        tty: self.tty,
+                    }
+                }
+            }
+        }
+    };
+}
+
 mod container;
 mod exec;
 mod image;
@@ -35,9 +202,121 @@ pub use service::*;
 #[cfg(feature = "swarm")]
 pub use swarm::*;
 
-use containers_api::{impl_opts_builder, impl_url_bool_field, impl_url_field};
+use containers_api::{impl_url_bool_field, impl_url_field};
+
+use std::collections::BTreeMap;
+
+/// Filters used by list endpoints, encoded as a JSON object mapping each key to a list of
+/// values, for example `filters={"label":["env=prod"],"status":["running"]}`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Filters(BTreeMap<String, Vec<String>>);
+
+impl Filters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a `value` for `key`. Values added for the same key are combined.
+    pub fn add<K, V>(&mut self, key: K, value: V) -> &mut Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.0.entry(key.into()).or_default().push(value.into());
+        self
+    }
+
+    /// Returns `true` if no filter was added.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the filters as a JSON string.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.0).unwrap_or_default()
+    }
+
+    /// Returns the URL encoded `filters` query parameter.
+    pub fn encode(&self) -> String {
+        containers_api::url::encoded_pair("filters", self.to_json())
+    }
+}
+
+/// A point in time used by the `since` and `until` parameters of logs and events and by prune
+/// filters, sent to Docker as seconds since the UNIX epoch.
+///
+/// Can be created from seconds since the epoch, a [`SystemTime`](std::time::SystemTime) or with
+/// the `chrono` feature from a `chrono::DateTime`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(i64);
+
+impl Timestamp {
+    /// Creates a timestamp from seconds since the UNIX epoch.
+    pub fn from_secs(secs: i64) -> Self {
+        Self(secs)
+    }
+
+    /// Returns the seconds since the UNIX epoch.
+    pub fn secs(&self) -> i64 {
+        self.0
+    }
+}
+
+impl From<i64> for Timestamp {
+    fn from(secs: i64) -> Self {
+        Self(secs)
+    }
+}
+
+impl From<std::time::SystemTime> for Timestamp {
+    fn from(time: std::time::SystemTime) -> Self {
+        match time.duration_since(std::time::UNIX_EPOCH) {
+            Ok(since_epoch) => Self(since_epoch.as_secs() as i64),
+            Err(e) => Self(-(e.duration().as_secs() as i64)),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for Timestamp {
+    fn from(time: chrono::DateTime<Tz>) -> Self {
+        Self(time.timestamp())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<&chrono::DateTime<Tz>> for Timestamp {
+    fn from(time: &chrono::DateTime<Tz>) -> Self {
+        Self(time.timestamp())
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Number of log lines to return, counted from the end of the logs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Tail {
+    /// Return all log lines, this is the default.
+    #[default]
+    All,
+    /// Return this number of lines.
+    Lines(u32),
+}
+
+impl std::fmt::Display for Tail {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::All => write!(f, "all"),
+            Self::Lines(lines) => write!(f, "{}", lines),
+        }
+    }
+}
 
-impl_opts_builder!(url => Logs);
+impl_output_opts_builder!(Logs);
 
 impl LogsOptsBuilder {
     impl_url_bool_field!(
@@ -66,51 +345,204 @@ impl LogsOptsBuilder {
     );
 
     /// Return all log lines.
-    pub fn all(mut self) -> Self {
-        self.params.insert("tail", "all".into());
-        self
+    pub fn all(self) -> Self {
+        self.tail(Tail::All)
     }
 
-    #[cfg(feature = "chrono")]
-    /// Only return logs since this time.
-    pub fn since<Tz>(mut self, timestamp: &chrono::DateTime<Tz>) -> Self
-    where
-        Tz: chrono::TimeZone,
-    {
-        self.params
-            .insert("since", timestamp.timestamp().to_string());
+    /// Number of log lines to return from the end of the logs. All lines are returned by
+    /// default.
+    pub fn tail(mut self, tail: Tail) -> Self {
+        self.params.insert("tail", tail.to_string());
         self
     }
 
-    #[cfg(not(feature = "chrono"))]
-    /// Only return logs since this time, as a UNIX timestamp.
-    pub fn since(mut self, timestamp: i64) -> Self {
-        self.params.insert("since", timestamp.to_string());
+    /// Only return logs since this time, for example a UNIX timestamp in seconds or a
+    /// [`SystemTime`](std::time::SystemTime).
+    pub fn since<T>(mut self, timestamp: T) -> Self
+    where
+        T: Into<Timestamp>,
+    {
+        self.params.insert("since", timestamp.into().to_string());
         self
     }
 
-    #[cfg(feature = "chrono")]
-    /// Only return logs before this time.
-    pub fn until<Tz>(mut self, timestamp: &chrono::DateTime<Tz>) -> Self
+    /// Only return logs before this time, for example a UNIX timestamp in seconds or a
+    /// [`SystemTime`](std::time::SystemTime).
+    pub fn until<T>(mut self, timestamp: T) -> Self
     where
-        Tz: chrono::TimeZone,
+        T: Into<Timestamp>,
     {
-        self.params
-            .insert("until", timestamp.timestamp().to_string());
+        self.params.insert("until", timestamp.into().to_string());
         self
     }
+}
 
-    #[cfg(not(feature = "chrono"))]
-    /// Only return logs before this time, as a UNIX timestamp.
-    pub fn until(mut self, timestamp: i64) -> Self {
-        self.params.insert("until", timestamp.to_string());
-        self
+/// Validates that `platform` is in the format `os/arch[/variant]`, for example `linux/arm64`.
+pub(crate) fn validate_platform(platform: &str) -> crate::Result<()> {
+    let parts: Vec<_> = platform.split('/').collect();
+    let valid = matches!(parts.len(), 2 | 3)
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && !part.contains(char::is_whitespace));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(crate::Error::InvalidPlatform(platform.to_owned()))
+    }
+}
+
+/// Validates that `keys` is a comma separated sequence of keys used to detach from a container,
+/// where each key is a single character or `ctrl-<value>` and `<value>` is one of `a-z`, `@`,
+/// `[`, `\`, `]`, `^` or `_`. For example `ctrl-p,ctrl-q`.
+pub(crate) fn validate_detach_keys(keys: &str) -> crate::Result<()> {
+    let valid = keys.split(',').all(|key| {
+        if key.len() == 1 {
+            return key.is_ascii();
+        }
+        matches!(
+            key.to_ascii_lowercase()
+                .strip_prefix("ctrl-")
+                .map(str::as_bytes),
+            Some([b'a'..=b'z' | b'@' | b'[' | b'\\' | b']' | b'^' | b'_'])
+        )
+    });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(crate::Error::InvalidDetachKeys(keys.to_owned()))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use containers_api::impl_opts_builder;
+
+    #[test]
+    fn filters_multiple_values() {
+        let mut filters = Filters::new();
+        filters
+            .add("label", "env=prod")
+            .add("status", "running")
+            .add("label", "tier=web");
+
+        assert_eq!(
+            filters.to_json(),
+            r#"{"label":["env=prod","tier=web"],"status":["running"]}"#
+        );
+        assert_eq!(
+            filters.encode(),
+            "filters=%7B%22label%22%3A%5B%22env%3Dprod%22%2C%22tier%3Dweb%22%5D%2C%22status%22%3A%5B%22running%22%5D%7D"
+        );
+        assert!(Filters::new().is_empty());
+    }
+
+    impl_opts_builder!(url => QueryTest);
+
+    impl QueryTestOptsBuilder {
+        impl_query_field!(all: bool => "all");
+        impl_query_field!(limit: usize => "limit");
+    }
+
+    #[test]
+    fn query_field_bool() {
+        let opts = QueryTestOpts::builder().all(true).build();
+        assert_eq!(opts.serialize(), Some("all=true".into()));
+
+        let opts = QueryTestOpts::builder().all(false).limit(5).build();
+        assert_eq!(opts.serialize(), Some("all=false&limit=5".into()));
+
+        assert_eq!(QueryTestOpts::builder().build().serialize(), None);
+    }
+
+    #[test]
+    fn filters_escaping() {
+        let mut filters = Filters::new();
+        filters.add("name", "a b&c=\"d\"/é");
+
+        let encoded = filters.encode();
+        let value = encoded.strip_prefix("filters=").unwrap();
+        assert!(!value.contains(&['&', '=', ' ', '"', '/'][..]));
+
+        let (key, value) = url::form_urlencoded::parse(encoded.as_bytes())
+            .into_owned()
+            .next()
+            .unwrap();
+        assert_eq!(key, "filters");
+        assert_eq!(value, r#"{"name":["a b&c=\"d\"/é"]}"#);
+    }
+
+    #[test]
+    fn platform_validation() {
+        assert!(validate_platform("linux/amd64").is_ok());
+        assert!(validate_platform("linux/arm64/v8").is_ok());
+
+        for platform in [
+            "arm64",
+            "linux/",
+            "/arm64",
+            "linux/arm64/v8/x",
+            "linux/arm 64",
+            "",
+        ] {
+            match validate_platform(platform) {
+                Err(crate::Error::InvalidPlatform(p)) => assert_eq!(p, platform),
+                res => panic!("Expected Error::InvalidPlatform, got {:?}", res),
+            }
+        }
+    }
+
+    #[test]
+    fn detach_keys_validation() {
+        for keys in ["ctrl-a,a", "ctrl-p,ctrl-q", "ctrl-@", "CTRL-X", "q"] {
+            assert!(validate_detach_keys(keys).is_ok(), "{}", keys);
+        }
+
+        for keys in [
+            "bogus", "", "ctrl-", "ctrl-1", "ctrl-ab", "ctrl-a,", "alt-a",
+        ] {
+            match validate_detach_keys(keys) {
+                Err(crate::Error::InvalidDetachKeys(k)) => assert_eq!(k, keys),
+                res => panic!("Expected Error::InvalidDetachKeys, got {:?}", res),
+            }
+        }
+    }
+
+    #[test]
+    fn timestamp_conversion() {
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        let time = UNIX_EPOCH + Duration::from_millis(1_600_000_000_999);
+        assert_eq!(Timestamp::from(time).secs(), 1_600_000_000);
+        assert_eq!(
+            Timestamp::from(UNIX_EPOCH - Duration::from_secs(60)).secs(),
+            -60
+        );
+        assert_eq!(Timestamp::from(1_600_000_000).to_string(), "1600000000");
+
+        let options = LogsOptsBuilder::default()
+            .since(time)
+            .until(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+            .build();
+        let serialized = options.serialize().unwrap();
+        assert!(serialized.contains("since=1600000000"));
+        assert!(serialized.contains("until=1700000000"));
+    }
+
+    #[test]
+    fn logs_tail() {
+        let options = LogsOptsBuilder::default().tail(Tail::Lines(100)).build();
+        assert_eq!(options.serialize(), Some("tail=100".to_string()));
+
+        let options = LogsOptsBuilder::default().tail(Tail::All).build();
+        assert_eq!(options.serialize(), Some("tail=all".to_string()));
+
+        assert_eq!(Tail::default(), Tail::All);
+        assert_eq!(LogsOptsBuilder::default().build().serialize(), None);
+    }
+
     #[cfg(feature = "chrono")]
     #[test]
     fn logs_options() {
@@ -1,4 +1,8 @@
-use crate::{models::Ipam, Error, Result};
+use crate::{
+    models::{Ipam, IpamConfig},
+    opts::Timestamp,
+    Error, Result,
+};
 use containers_api::opts::{Filter, FilterItem};
 use containers_api::{
     impl_field, impl_filter_func, impl_map_field, impl_opts_builder, impl_str_field, impl_vec_field,
@@ -6,19 +10,24 @@ use containers_api::{
 
 use std::{collections::HashMap, convert::AsRef};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 impl_opts_builder!(url =>
-    /// Options for filtering networks list results"
+    /// Options for filtering networks list results
     NetworkList
 );
 
-/// Used for [`NetworkFilter::Scope`](NetworkFilter::Scope).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+/// Scope of a network. Used for [`NetworkFilter::Scope`](NetworkFilter::Scope) and returned by
+/// [`NetworkDetails::scope`](crate::models::NetworkDetails::scope).
 pub enum Scope {
     Swarm,
     Global,
     Local,
+    /// A scope unknown to this version of the library.
+    Other(String),
 }
 
 impl AsRef<str> for Scope {
@@ -27,10 +36,81 @@ impl AsRef<str> for Scope {
             Scope::Swarm => "swarm",
             Scope::Global => "global",
             Scope::Local => "local",
+            Scope::Other(scope) => scope,
+        }
+    }
+}
+
+impl From<String> for Scope {
+    fn from(scope: String) -> Self {
+        match scope.as_str() {
+            "swarm" => Scope::Swarm,
+            "global" => Scope::Global,
+            "local" => Scope::Local,
+            _ => Scope::Other(scope),
+        }
+    }
+}
+
+impl From<Scope> for String {
+    fn from(scope: Scope) -> Self {
+        scope.as_ref().to_owned()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+/// Driver of a network. Can be passed to
+/// [`NetworkCreateOptsBuilder::driver`](NetworkCreateOptsBuilder::driver) and is returned by
+/// [`NetworkDetails::driver`](crate::models::NetworkDetails::driver).
+pub enum NetworkDriver {
+    Bridge,
+    Host,
+    Overlay,
+    Macvlan,
+    Ipvlan,
+    /// The driver of the builtin `none` network.
+    Null,
+    /// A network plugin or a driver unknown to this version of the library.
+    Other(String),
+}
+
+impl AsRef<str> for NetworkDriver {
+    fn as_ref(&self) -> &str {
+        match &self {
+            NetworkDriver::Bridge => "bridge",
+            NetworkDriver::Host => "host",
+            NetworkDriver::Overlay => "overlay",
+            NetworkDriver::Macvlan => "macvlan",
+            NetworkDriver::Ipvlan => "ipvlan",
+            NetworkDriver::Null => "null",
+            NetworkDriver::Other(driver) => driver,
+        }
+    }
+}
+
+impl From<String> for NetworkDriver {
+    fn from(driver: String) -> Self {
+        match driver.as_str() {
+            "bridge" => NetworkDriver::Bridge,
+            "host" => NetworkDriver::Host,
+            "overlay" => NetworkDriver::Overlay,
+            "macvlan" => NetworkDriver::Macvlan,
+            "ipvlan" => NetworkDriver::Ipvlan,
+            "null" => NetworkDriver::Null,
+            _ => NetworkDriver::Other(driver),
         }
     }
 }
 
+impl From<NetworkDriver> for String {
+    fn from(driver: NetworkDriver) -> Self {
+        driver.as_ref().to_owned()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// Used for [`NetworkFilter::Type`](NetworkFilter::Type).
 pub enum NetworkType {
     Custom,
     Builtin,
@@ -60,31 +140,40 @@ pub enum NetworkFilter {
     LabelKeyVal(String, String),
     /// Matches all or part of a network name.
     Name(String),
+    /// Matches a network's scope.
     Scope(Scope),
+    /// Filters networks by type, `custom` returns all user-defined networks.
     Type(NetworkType),
 }
 
-impl Filter for NetworkFilter {
-    fn query_item(&self) -> FilterItem {
+impl NetworkFilter {
+    fn key_val(&self) -> (&'static str, String) {
         use NetworkFilter::*;
 
         match &self {
-            Dangling(dangling) => FilterItem::new("dangling", dangling.to_string()),
-            Driver(driver) => FilterItem::new("driver", driver.to_owned()),
-            Id(id) => FilterItem::new("id", id.to_owned()),
-            LabelKey(key) => FilterItem::new("label", key.to_owned()),
-            LabelKeyVal(key, val) => FilterItem::new("label", format!("{}={}", key, val)),
-            Name(name) => FilterItem::new("name", name.to_owned()),
-            Scope(scope) => FilterItem::new("scope", scope.as_ref().to_owned()),
-            Type(type_) => FilterItem::new("type", type_.as_ref().to_owned()),
+            Dangling(dangling) => ("dangling", dangling.to_string()),
+            Driver(driver) => ("driver", driver.to_owned()),
+            Id(id) => ("id", id.to_owned()),
+            LabelKey(key) => ("label", key.to_owned()),
+            LabelKeyVal(key, val) => ("label", format!("{}={}", key, val)),
+            Name(name) => ("name", name.to_owned()),
+            Scope(scope) => ("scope", scope.as_ref().to_owned()),
+            Type(type_) => ("type", type_.as_ref().to_owned()),
         }
     }
 }
 
+impl Filter for NetworkFilter {
+    fn query_item(&self) -> FilterItem {
+        let (key, val) = self.key_val();
+        FilterItem::new(key, val)
+    }
+}
+
 impl NetworkListOptsBuilder {
-    impl_filter_func!(
+    impl_query_field!(
         /// Filter the list of networks by one of the variants of the filter.
-        NetworkFilter
+        filter: NetworkFilter => "filters"
     );
 }
 
@@ -107,6 +196,35 @@ impl NetworkCreateOpts {
     pub fn serialize(&self) -> Result<String> {
         serde_json::to_string(&self.params).map_err(Error::from)
     }
+
+    /// Rejects combinations of options that the daemon would refuse.
+    fn validate(&self) -> Result<()> {
+        let driver = self
+            .params
+            .get("Driver")
+            .and_then(Value::as_str)
+            .unwrap_or("bridge");
+        let is_set = |key: &str| {
+            self.params
+                .get(key)
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
+        };
+
+        if is_set("Ingress") && driver != "overlay" {
+            return Err(Error::InvalidNetworkOpts(format!(
+                "an ingress network requires the `overlay` driver, got `{}`",
+                driver
+            )));
+        }
+        if is_set("Ingress") && is_set("Attachable") {
+            return Err(Error::InvalidNetworkOpts(
+                "an ingress network can't be attachable".into(),
+            ));
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Default)]
@@ -131,7 +249,8 @@ impl NetworkCreateOptsBuilder {
     );
 
     impl_str_field!(
-        /// Name of the network driver plugin to use.
+        /// Name of the network driver plugin to use, for example a
+        /// [`NetworkDriver`](NetworkDriver).
         driver => "Driver"
     );
 
@@ -166,16 +285,86 @@ impl NetworkCreateOptsBuilder {
         labels => "Labels"
     );
 
+    impl_add_label!(NetworkCreateOptsBuilder);
+
     impl_field!(
         /// IP Address Management configuration
         ipam: Ipam => "IPAM"
     );
 
-    pub fn build(&self) -> NetworkCreateOpts {
-        NetworkCreateOpts {
-            params: self.params.clone(),
+    /// Add a single IP Address Management configuration entry (subnet, gateway, IP range...).
+    /// Can be called multiple times, for example to create a dual-stack IPv4/IPv6 network.
+    /// Entries are appended to the configuration set with [`ipam`](NetworkCreateOptsBuilder::ipam).
+    pub fn ipam_config(mut self, config: IpamConfig) -> Self {
+        let ipam = self.params.entry("IPAM").or_insert_with(|| json!({}));
+        if let Some(ipam) = ipam.as_object_mut() {
+            let configs = ipam.entry("Config").or_insert_with(|| json!([]));
+            if let Some(configs) = configs.as_array_mut() {
+                configs.push(json!(config));
+            }
+        }
+        self
+    }
+
+    /// Subnet of the network in CIDR format, for example `10.0.0.0/24`. Sets the subnet of the
+    /// first IP Address Management configuration entry, use
+    /// [`ipam_config`](NetworkCreateOptsBuilder::ipam_config) to add more entries.
+    pub fn subnet<S>(mut self, subnet: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.set_ipam_config_field("Subnet", subnet.into());
+        self
+    }
+
+    /// IPv4 or IPv6 gateway of the subnet set with [`subnet`](NetworkCreateOptsBuilder::subnet).
+    pub fn gateway<S>(mut self, gateway: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.set_ipam_config_field("Gateway", gateway.into());
+        self
+    }
+
+    /// Range of the subnet set with [`subnet`](NetworkCreateOptsBuilder::subnet) that container
+    /// addresses are allocated from, in CIDR format.
+    pub fn ip_range<S>(mut self, ip_range: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.set_ipam_config_field("IPRange", ip_range.into());
+        self
+    }
+
+    /// Sets `key` of the first entry of `IPAM.Config`, creating the entry if necessary.
+    fn set_ipam_config_field(&mut self, key: &str, value: String) {
+        let ipam = self.params.entry("IPAM").or_insert_with(|| json!({}));
+        if let Some(ipam) = ipam.as_object_mut() {
+            let configs = ipam.entry("Config").or_insert_with(|| json!([]));
+            if configs.is_null() {
+                *configs = json!([]);
+            }
+            if let Some(configs) = configs.as_array_mut() {
+                if configs.is_empty() {
+                    configs.push(json!({}));
+                }
+                if let Some(config) = configs[0].as_object_mut() {
+                    config.insert(key.to_owned(), Value::String(value));
+                }
+            }
         }
     }
+
+    /// Finish building NetworkCreateOpts. Returns
+    /// [`Error::InvalidNetworkOpts`](crate::Error::InvalidNetworkOpts) if the options contain
+    /// a combination the daemon would refuse, like an attachable ingress network.
+    pub fn build(&self) -> Result<NetworkCreateOpts> {
+        let opts = NetworkCreateOpts {
+            params: self.params.clone(),
+        };
+        opts.validate()?;
+        Ok(opts)
+    }
 }
 #[derive(Serialize, Debug)]
 /// Interface for disconnecting a container from a network.
@@ -241,6 +430,13 @@ impl ContainerConnectionOpts {
     {
         ContainerConnectionOptsBuilder::new(container_id.as_ref())
     }
+
+    pub(crate) fn container(&self) -> &str {
+        self.params
+            .get("Container")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Default)]
@@ -257,15 +453,22 @@ impl ContainerConnectionOptsBuilder {
         }
     }
 
-    /// Endpoint's IPAM configuration.
+    /// Endpoint's IPAM configuration. Use this to assign a static IPv4/IPv6 address or
+    /// link-local addresses to the container on this network.
     pub fn ipam_config(mut self, config: EndpointIpamConfig) -> Self {
-        self.params.insert("EndpointConfig", json!(config.params));
+        self.params.insert("IPAMConfig", json!(config.params));
         self
     }
 
-    impl_vec_field!(aliases => "Aliases");
+    impl_vec_field!(
+        /// Network-scoped aliases of the container.
+        aliases => "Aliases"
+    );
 
-    impl_vec_field!(links => "Links");
+    impl_vec_field!(
+        /// Links to other containers in the form of `container:alias`.
+        links => "Links"
+    );
 
     impl_str_field!(
         /// Unique ID of the network.
@@ -326,9 +529,55 @@ impl ContainerConnectionOptsBuilder {
     }
 }
 
+#[derive(Clone, Debug, Default)]
+/// Configuration of the endpoint of a container on a network when creating the container, see
+/// [`ContainerCreateOptsBuilder::networks`](crate::opts::ContainerCreateOptsBuilder::networks).
+/// The endpoint is configured like with [`ContainerConnectionOptsBuilder`].
+pub struct EndpointConfig {
+    params: HashMap<&'static str, Value>,
+}
+
+impl EndpointConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Endpoint's IPAM configuration. Use this to assign a static IPv4/IPv6 address or
+    /// link-local addresses to the container on this network.
+    pub fn ipam_config(mut self, config: EndpointIpamConfig) -> Self {
+        self.params.insert("IPAMConfig", json!(config.params));
+        self
+    }
+
+    impl_vec_field!(
+        /// Network-scoped aliases of the container.
+        aliases => "Aliases"
+    );
+
+    impl_vec_field!(
+        /// Links to other containers in the form of `container:alias`.
+        links => "Links"
+    );
+
+    impl_str_field!(
+        /// MAC address for the endpoint on this network.
+        mac => "MacAddress"
+    );
+
+    impl_map_field!(json
+        /// DriverOpts is a mapping of driver options and values. These options are passed directly
+        /// to the driver and are driver specific.
+        driver_opts => "DriverOpts"
+    );
+
+    pub(crate) fn to_json(&self) -> Value {
+        json!(self.params)
+    }
+}
+
 #[derive(Default)]
 /// Used to configure endpoint IPAM configuration when connection a container to a network.
-/// See [`ipam_config`](ContainerConnectOptsBuilder::ipam_config).
+/// See [`ipam_config`](ContainerConnectionOptsBuilder::ipam_config).
 pub struct EndpointIpamConfig {
     params: HashMap<&'static str, serde_json::Value>,
 }
@@ -378,6 +627,8 @@ pub enum NetworkPruneFilter {
     #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     /// Prune networks created before this timestamp. Same as `Until` but takes a datetime object.
     UntilDate(chrono::DateTime<chrono::Utc>),
+    /// Prune networks created before this timestamp. Same as `Until` but takes a [`Timestamp`].
+    UntilTimestamp(Timestamp),
     /// Label in the form of `label=key`.
     LabelKey(String),
     /// Label in the form of `label=key=val`.
@@ -391,6 +642,7 @@ impl Filter for NetworkPruneFilter {
             Until(until) => FilterItem::new("until", until.to_owned()),
             #[cfg(feature = "chrono")]
             UntilDate(until) => FilterItem::new("until", until.timestamp().to_string()),
+            UntilTimestamp(until) => FilterItem::new("until", until.to_string()),
             LabelKey(label) => FilterItem::new("label", label.to_owned()),
             Label(key, val) => FilterItem::new("label", format!("{}={}", key, val)),
         }
@@ -403,3 +655,328 @@ impl NetworkPruneOptsBuilder {
         NetworkPruneFilter
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn network_create_add_label() {
+        let opts = NetworkCreateOpts::builder("test")
+            .labels([("app", "test")])
+            .add_label("env", "prod")
+            .add_label("tier", "backend")
+            .build()
+            .unwrap();
+
+        let body: Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(
+            body["Labels"],
+            json!({"app": "test", "env": "prod", "tier": "backend"})
+        );
+    }
+
+    #[test]
+    fn network_create_opts_validation() {
+        let opts = NetworkCreateOpts::builder("ingress")
+            .driver("overlay")
+            .ingress(true)
+            .build();
+        assert!(opts.is_ok());
+
+        let opts = NetworkCreateOpts::builder("ingress")
+            .driver("bridge")
+            .ingress(true)
+            .build();
+        match opts {
+            Err(Error::InvalidNetworkOpts(msg)) => assert!(msg.contains("`bridge`")),
+            res => panic!("Expected Error::InvalidNetworkOpts, got {:?}", res),
+        }
+
+        let opts = NetworkCreateOpts::builder("ingress")
+            .driver("overlay")
+            .ingress(true)
+            .attachable(true)
+            .build();
+        assert!(matches!(opts, Err(Error::InvalidNetworkOpts(_))));
+    }
+
+    fn decoded_filters(serialized: &str) -> HashMap<String, Vec<String>> {
+        let (key, val) = url::form_urlencoded::parse(serialized.as_bytes())
+            .next()
+            .expect("filters query pair");
+        assert_eq!(key, "filters");
+        serde_json::from_str(&val).expect("filters json")
+    }
+
+    #[test]
+    fn network_list_filters() {
+        let opts = NetworkListOpts::builder()
+            .filter([
+                NetworkFilter::Driver("overlay".into()),
+                NetworkFilter::Dangling(true),
+            ])
+            .build();
+
+        let filters = decoded_filters(&opts.serialize().unwrap());
+        assert_eq!(filters.len(), 2);
+        assert_eq!(filters["driver"], vec!["overlay"]);
+        assert_eq!(filters["dangling"], vec!["true"]);
+
+        let opts = NetworkListOpts::builder()
+            .filter([
+                NetworkFilter::Scope(Scope::Swarm),
+                NetworkFilter::Type(NetworkType::Custom),
+                NetworkFilter::LabelKeyVal("env".into(), "prod".into()),
+            ])
+            .build();
+
+        let filters = decoded_filters(&opts.serialize().unwrap());
+        assert_eq!(filters["scope"], vec!["swarm"]);
+        assert_eq!(filters["type"], vec!["custom"]);
+        assert_eq!(filters["label"], vec!["env=prod"]);
+
+        let opts = NetworkListOpts::builder()
+            .filter([
+                NetworkFilter::LabelKey("env".into()),
+                NetworkFilter::LabelKeyVal("tier".into(), "web".into()),
+            ])
+            .build();
+
+        let filters = decoded_filters(&opts.serialize().unwrap());
+        assert_eq!(filters["label"], vec!["env", "tier=web"]);
+    }
+
+    #[test]
+    fn network_list_filters_accumulate() {
+        let opts = NetworkListOpts::builder()
+            .filter([NetworkFilter::Driver("overlay".into())])
+            .filter([
+                NetworkFilter::LabelKey("env".into()),
+                NetworkFilter::Driver("bridge".into()),
+            ])
+            .build();
+
+        let filters = decoded_filters(&opts.serialize().unwrap());
+        assert_eq!(filters.len(), 2);
+        assert_eq!(filters["driver"], vec!["overlay", "bridge"]);
+        assert_eq!(filters["label"], vec!["env"]);
+    }
+
+    #[test]
+    fn network_prune_filters() {
+        let opts = NetworkPruneOpts::builder()
+            .filter([
+                NetworkPruneFilter::Label("env".into(), "test".into()),
+                NetworkPruneFilter::Until("1h30m".into()),
+            ])
+            .build();
+
+        let filters = decoded_filters(&opts.serialize().unwrap());
+        assert_eq!(filters["label"], vec!["env=test"]);
+        assert_eq!(filters["until"], vec!["1h30m"]);
+
+        assert_eq!(
+            containers_api::url::construct_ep("/networks/prune", opts.serialize()),
+            format!("/networks/prune?{}", opts.serialize().unwrap())
+        );
+        let opts = NetworkPruneOpts::builder().build();
+        assert!(opts.serialize().is_none());
+        assert_eq!(
+            containers_api::url::construct_ep("/networks/prune", opts.serialize()),
+            "/networks/prune"
+        );
+    }
+
+    #[test]
+    fn network_create_flags() {
+        let opts = NetworkCreateOpts::builder("test-net")
+            .enable_ipv6(true)
+            .internal(true)
+            .attachable(false)
+            .ingress(false)
+            .options([("com.docker.network.bridge.name", "docker1")])
+            .build()
+            .unwrap();
+
+        let serialized: Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(serialized["EnableIPv6"], Value::Bool(true));
+        assert_eq!(serialized["Internal"], Value::Bool(true));
+        assert_eq!(serialized["Attachable"], Value::Bool(false));
+        assert_eq!(serialized["Ingress"], Value::Bool(false));
+        assert_eq!(
+            serialized["Options"],
+            json!({"com.docker.network.bridge.name": "docker1"})
+        );
+    }
+
+    #[test]
+    fn container_connection_endpoint_config() {
+        let opts = ContainerConnectionOpts::builder("test-container")
+            .aliases(["web"])
+            .ipam_config(
+                EndpointIpamConfig::new()
+                    .ipv4("172.20.0.5")
+                    .link_local_ips(["169.254.0.5"]),
+            )
+            .driver_opts([("opt", "val")])
+            .build();
+
+        let serialized: Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(
+            serialized,
+            json!({
+                "Container": "test-container",
+                "EndpointConfig": {
+                    "Aliases": ["web"],
+                    "IPAMConfig": {
+                        "IPv4Address": "172.20.0.5",
+                        "LinkLocalIPs": ["169.254.0.5"]
+                    },
+                    "DriverOpts": {"opt": "val"}
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn container_connection_fields_accumulate() {
+        let opts = ContainerConnectionOpts::builder("test-container")
+            .ipam_config(EndpointIpamConfig::new().ipv6("2001:db8::5"))
+            .aliases(["web", "frontend"])
+            .ipv4("172.20.0.5")
+            .mac("02:42:ac:11:00:02")
+            .build();
+
+        let serialized: Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        let endpoint_config = &serialized["EndpointConfig"];
+        assert_eq!(endpoint_config["Aliases"], json!(["web", "frontend"]));
+        assert_eq!(endpoint_config["IPAddress"], json!("172.20.0.5"));
+        assert_eq!(endpoint_config["MacAddress"], json!("02:42:ac:11:00:02"));
+        assert_eq!(
+            endpoint_config["IPAMConfig"],
+            json!({"IPv6Address": "2001:db8::5"})
+        );
+    }
+
+    fn ipam_config(subnet: &str, gateway: &str) -> IpamConfig {
+        IpamConfig {
+            auxiliary_addresses: None,
+            gateway: Some(gateway.into()),
+            ip_range: None,
+            subnet: Some(subnet.into()),
+        }
+    }
+
+    #[test]
+    fn network_create_ipam_config() {
+        let opts = NetworkCreateOpts::builder("test-net")
+            .driver("overlay")
+            .ipam_config(ipam_config("10.0.0.0/24", "10.0.0.1"))
+            .build()
+            .unwrap();
+
+        let serialized: Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(
+            serialized,
+            json!({
+                "Name": "test-net",
+                "Driver": "overlay",
+                "IPAM": {
+                    "Config": [{"Subnet": "10.0.0.0/24", "Gateway": "10.0.0.1"}]
+                }
+            })
+        );
+
+        let opts = NetworkCreateOpts::builder("test-net")
+            .ipam(Ipam {
+                config: None,
+                driver: Some("default".into()),
+                options: None,
+            })
+            .ipam_config(ipam_config("10.0.0.0/24", "10.0.0.1"))
+            .ipam_config(ipam_config("2001:db8::/64", "2001:db8::1"))
+            .build()
+            .unwrap();
+
+        let serialized: Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(
+            serialized["IPAM"],
+            json!({
+                "Driver": "default",
+                "Config": [
+                    {"Subnet": "10.0.0.0/24", "Gateway": "10.0.0.1"},
+                    {"Subnet": "2001:db8::/64", "Gateway": "2001:db8::1"}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn network_create_subnet() {
+        let opts = NetworkCreateOpts::builder("test-net")
+            .driver("overlay")
+            .subnet("10.0.0.0/24")
+            .gateway("10.0.0.1")
+            .ip_range("10.0.0.128/25")
+            .build()
+            .unwrap();
+
+        let serialized: Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(
+            serialized["IPAM"],
+            json!({
+                "Config": [{
+                    "Subnet": "10.0.0.0/24",
+                    "Gateway": "10.0.0.1",
+                    "IPRange": "10.0.0.128/25"
+                }]
+            })
+        );
+
+        // the convenience setters update the first entry, further entries are kept
+        let opts = NetworkCreateOpts::builder("test-net")
+            .ipam(Ipam {
+                config: None,
+                driver: Some("default".into()),
+                options: None,
+            })
+            .gateway("10.0.0.1")
+            .ipam_config(ipam_config("2001:db8::/64", "2001:db8::1"))
+            .subnet("10.0.0.0/24")
+            .build()
+            .unwrap();
+
+        let serialized: Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(
+            serialized["IPAM"],
+            json!({
+                "Driver": "default",
+                "Config": [
+                    {"Subnet": "10.0.0.0/24", "Gateway": "10.0.0.1"},
+                    {"Subnet": "2001:db8::/64", "Gateway": "2001:db8::1"}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn scope_and_driver_serde() {
+        let scope: Scope = serde_json::from_str(r#""swarm""#).unwrap();
+        assert_eq!(scope, Scope::Swarm);
+        let scope: Scope = serde_json::from_str(r#""cluster""#).unwrap();
+        assert_eq!(scope, Scope::Other("cluster".into()));
+        assert_eq!(serde_json::to_string(&scope).unwrap(), r#""cluster""#);
+
+        let driver: NetworkDriver = serde_json::from_str(r#""null""#).unwrap();
+        assert_eq!(driver, NetworkDriver::Null);
+        let driver: NetworkDriver = serde_json::from_str(r#""weaveworks/net-plugin""#).unwrap();
+        assert_eq!(driver, NetworkDriver::Other("weaveworks/net-plugin".into()));
+
+        let opts = NetworkCreateOpts::builder("test")
+            .driver(NetworkDriver::Overlay)
+            .build()
+            .unwrap();
+        assert_eq!(opts.params["Driver"], "overlay");
+    }
+}
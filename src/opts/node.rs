@@ -26,21 +26,48 @@ impl AsRef<str> for Membership {
 
 #[derive(Serialize, Debug)]
 pub struct NodeUpdateOpts {
-    version: String,
+    version: u64,
     params: HashMap<&'static str, serde_json::Value>,
 }
 
 impl NodeUpdateOpts {
-    /// return a new instance of a builder for Opts
-    pub fn builder<V: Into<String>>(version: V) -> NodeUpdateOptsBuilder {
+    /// Returns a builder for updating a node. The `version` must be the current version of the
+    /// node object as returned by [`Node::inspect`](crate::Node::inspect), it is used to avoid
+    /// conflicting writes.
+    pub fn builder(version: u64) -> NodeUpdateOptsBuilder {
         NodeUpdateOptsBuilder::new(version)
     }
 
+    pub fn serialize(&self) -> Result<String> {
+        serde_json::to_string(&self.params).map_err(Error::from)
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct NodeUpdateOptsBuilder {
+    version: u64,
+    params: HashMap<&'static str, serde_json::Value>,
+}
+
+impl NodeUpdateOptsBuilder {
+    pub fn new(version: u64) -> Self {
+        Self {
+            version,
+            params: HashMap::new(),
+        }
+    }
+
     impl_map_field!(json
         /// User-defined key/value metadata
         labels => "Labels"
     );
 
+    impl_add_label!(NodeUpdateOptsBuilder);
+
     impl_str_field!(
         /// Name for the node.
         name => "Name"
@@ -56,26 +83,10 @@ impl NodeUpdateOpts {
         availability: NodeSpecAvailabilityInlineItem => "Availability"
     );
 
-    pub fn serialize(&self) -> Result<String> {
-        serde_json::to_string(&self.params).map_err(Error::from)
-    }
-
-    pub fn version(&self) -> &str {
-        &self.version
-    }
-}
-
-#[derive(Serialize, Debug)]
-pub struct NodeUpdateOptsBuilder {
-    version: String,
-    params: HashMap<&'static str, serde_json::Value>,
-}
-
-impl NodeUpdateOptsBuilder {
-    pub fn new<V: Into<String>>(version: V) -> Self {
-        Self {
-            version: version.into(),
-            params: HashMap::new(),
+    pub fn build(self) -> NodeUpdateOpts {
+        NodeUpdateOpts {
+            version: self.version,
+            params: self.params,
         }
     }
 }
@@ -111,3 +122,23 @@ impl_opts_builder!(url => NodeList);
 impl NodeListOptsBuilder {
     impl_filter_func!(NodeFilter);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_update_opts() {
+        let opts = NodeUpdateOpts::builder(12)
+            .availability(NodeSpecAvailabilityInlineItem::Drain)
+            .role(NodeSpecRoleInlineItem::Worker)
+            .build();
+
+        assert_eq!(opts.version(), 12);
+        let body: serde_json::Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"Availability": "drain", "Role": "worker"})
+        );
+    }
+}
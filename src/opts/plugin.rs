@@ -1,5 +1,9 @@
+use crate::{models::PluginPrivilege, opts::RegistryAuth, Result};
 use containers_api::opts::{Filter, FilterItem};
-use containers_api::{impl_filter_func, impl_opts_builder};
+use containers_api::url::encoded_pairs;
+use containers_api::{impl_filter_func, impl_opts_builder, impl_url_str_field};
+
+use std::collections::HashMap;
 
 impl_opts_builder!(url => PluginList);
 
@@ -25,3 +29,82 @@ impl PluginListOptsBuilder {
         PluginFilter
     );
 }
+
+#[derive(Default, Debug, Clone)]
+/// Options for installing a plugin with [`Plugins::install`](crate::Plugins::install).
+pub struct PluginInstallOpts {
+    auth: Option<RegistryAuth>,
+    params: HashMap<&'static str, String>,
+    privileges: Vec<PluginPrivilege>,
+}
+
+impl PluginInstallOpts {
+    /// Returns a new instance of a builder for a plugin pulled from `remote`, for example
+    /// `vieux/sshfs:latest`.
+    pub fn builder(remote: impl Into<String>) -> PluginInstallOptsBuilder {
+        PluginInstallOptsBuilder::new(remote)
+    }
+
+    /// Serialize Opts as a string. Returns None if no Opts are defined.
+    pub fn serialize(&self) -> Option<String> {
+        if self.params.is_empty() {
+            None
+        } else {
+            Some(encoded_pairs(&self.params))
+        }
+    }
+
+    pub(crate) fn auth_header(&self) -> Option<String> {
+        self.auth.clone().map(|a| a.serialize())
+    }
+
+    pub(crate) fn privileges(&self) -> Result<String> {
+        serde_json::to_string(&self.privileges).map_err(Into::into)
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct PluginInstallOptsBuilder {
+    auth: Option<RegistryAuth>,
+    params: HashMap<&'static str, String>,
+    privileges: Vec<PluginPrivilege>,
+}
+
+impl PluginInstallOptsBuilder {
+    pub(crate) fn new(remote: impl Into<String>) -> Self {
+        let mut params = HashMap::new();
+        params.insert("remote", remote.into());
+        PluginInstallOptsBuilder {
+            params,
+            ..Default::default()
+        }
+    }
+
+    impl_url_str_field!(
+        /// Local name for the installed plugin. Defaults to the remote reference.
+        name => "name"
+    );
+
+    /// Privileges granted to the plugin. These have to match the privileges requested by the
+    /// plugin as returned by the daemon for the plugin to be installed.
+    pub fn privileges<P>(mut self, privileges: P) -> Self
+    where
+        P: IntoIterator<Item = PluginPrivilege>,
+    {
+        self.privileges = privileges.into_iter().collect();
+        self
+    }
+
+    pub fn auth(mut self, auth: RegistryAuth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn build(self) -> PluginInstallOpts {
+        PluginInstallOpts {
+            auth: self.auth,
+            params: self.params,
+            privileges: self.privileges,
+        }
+    }
+}
@@ -90,6 +90,56 @@ impl ServiceOptsBuilder {
         self
     }
 
+    impl_add_label!(ServiceOptsBuilder);
+
+    /// Image used by the containers of the service.
+    pub fn image<I>(self, image: I) -> Self
+    where
+        I: AsRef<str>,
+    {
+        self.insert_nested(
+            "TaskTemplate",
+            &["ContainerSpec", "Image"],
+            json!(image.as_ref()),
+        )
+    }
+
+    /// Environment variables of the containers of the service in the form of `KEY=value`.
+    pub fn env<E, S>(self, env: E) -> Self
+    where
+        E: IntoIterator<Item = S>,
+        S: AsRef<str> + Serialize,
+    {
+        let env: Vec<_> = env.into_iter().collect();
+        self.insert_nested("TaskTemplate", &["ContainerSpec", "Env"], json!(env))
+    }
+
+    /// Run the service in replicated mode with this number of tasks.
+    pub fn replicas(self, replicas: u64) -> Self {
+        self.insert_nested("Mode", &["Replicated", "Replicas"], json!(replicas))
+    }
+
+    /// Sets `value` at `path` below the top level `key` keeping other values set below `key`.
+    /// Values on the way that aren't objects are replaced by empty objects.
+    fn insert_nested(mut self, key: &'static str, path: &[&str], value: Value) -> Self {
+        if let Ok(root) = self.params.entry(key).or_insert_with(|| Ok(json!({}))) {
+            let mut node = root;
+            for segment in path {
+                if !node.is_object() {
+                    *node = json!({});
+                }
+                node = match node {
+                    Value::Object(object) => object.entry(*segment).or_insert_with(|| json!({})),
+                    _ => return self,
+                };
+            }
+            *node = value;
+        }
+        self
+    }
+
+    /// The full specification of the tasks of the service, this replaces values set with
+    /// [`image`](ServiceOptsBuilder::image) and [`env`](ServiceOptsBuilder::env).
     pub fn task_template(mut self, spec: &models::TaskSpec) -> Self {
         self.params.insert("TaskTemplate", to_value_result(spec));
         self
@@ -153,3 +203,51 @@ where
 {
     Ok(serde_json::to_value(value)?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn service_opts() {
+        let opts = ServiceOpts::builder()
+            .name("web")
+            .image("nginx:alpine")
+            .env(["A=1", "B=2"])
+            .replicas(3)
+            .build()
+            .unwrap();
+
+        let body: Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "Name": "web",
+                "TaskTemplate": {
+                    "ContainerSpec": {
+                        "Image": "nginx:alpine",
+                        "Env": ["A=1", "B=2"],
+                    }
+                },
+                "Mode": {"Replicated": {"Replicas": 3}},
+            })
+        );
+    }
+
+    #[test]
+    fn service_opts_replace_non_objects() {
+        let mut builder = ServiceOpts::builder().name("web");
+        builder.params.insert("Mode", Ok(json!("global")));
+        builder
+            .params
+            .insert("TaskTemplate", Ok(json!({"ContainerSpec": ["nginx"]})));
+        let opts = builder.image("nginx:alpine").replicas(2).build().unwrap();
+
+        let body: Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(
+            body["TaskTemplate"],
+            json!({"ContainerSpec": {"Image": "nginx:alpine"}})
+        );
+        assert_eq!(body["Mode"], json!({"Replicated": {"Replicas": 2}}));
+    }
+}
@@ -1,5 +1,9 @@
-use crate::models::SwarmSpec;
-use containers_api::{impl_field, impl_opts_builder, impl_str_field, impl_vec_field};
+use crate::{models::SwarmSpec, Error, Result};
+use containers_api::{
+    impl_field, impl_opts_builder, impl_str_field, impl_url_bool_field, impl_vec_field,
+};
+
+use std::collections::HashMap;
 
 impl_opts_builder!(json => SwarmJoin);
 
@@ -11,10 +15,10 @@ impl SwarmJoinOptsBuilder {
     );
 
     impl_str_field!(
-        // Externally reachable address advertised to other nodes. This can either be an address/port
-        // combination in the form 192.168.1.1:4567, or an interface followed by a port number, like eth0:4567.
-        // If the port number is omitted, the port number from the listen address is used. If AdvertiseAddr is
-        // not specified, it will be automatically detected when possible.
+        /// Externally reachable address advertised to other nodes. This can either be an address/port
+        /// combination in the form 192.168.1.1:4567, or an interface followed by a port number, like eth0:4567.
+        /// If the port number is omitted, the port number from the listen address is used. If AdvertiseAddr is
+        /// not specified, it will be automatically detected when possible.
         advertise_addr => "AdvertiseAddr"
     );
 
@@ -38,16 +42,16 @@ impl_opts_builder!(json => SwarmInit);
 
 impl SwarmInitOptsBuilder {
     impl_str_field!(
-        // Listen address used for inter-manager communication if the node gets promoted to manager,
-        // as well as determining the networking interface used for the VXLAN Tunnel Endpoint (VTEP).
+        /// Listen address used for inter-manager communication if the node gets promoted to manager,
+        /// as well as determining the networking interface used for the VXLAN Tunnel Endpoint (VTEP).
         listen_addr => "ListenAddr"
     );
 
     impl_str_field!(
-        // Externally reachable address advertised to other nodes. This can either be an address/port
-        // combination in the form 192.168.1.1:4567, or an interface followed by a port number, like eth0:4567.
-        // If the port number is omitted, the port number from the listen address is used. If AdvertiseAddr is
-        // not specified, it will be automatically detected when possible.
+        /// Externally reachable address advertised to other nodes. This can either be an address/port
+        /// combination in the form 192.168.1.1:4567, or an interface followed by a port number, like eth0:4567.
+        /// If the port number is omitted, the port number from the listen address is used. If AdvertiseAddr is
+        /// not specified, it will be automatically detected when possible.
         advertise_addr => "AdvertiseAddr"
     );
 
@@ -57,13 +61,13 @@ impl SwarmInitOptsBuilder {
     );
 
     impl_field!(
-        // Specifies the data path port number for data traffic. Acceptable port range is 1024 to 49151.
-        // If no port is set or is set to 0, default port 4789 will be used.
+        /// Specifies the data path port number for data traffic. Acceptable port range is 1024 to 49151.
+        /// If no port is set or is set to 0, default port 4789 will be used.
         data_path_port: u32 => "DataPathPort"
     );
 
     impl_vec_field!(
-        // Default Address Pool specifies default subnet pools for global scope networks.
+        /// Default Address Pool specifies default subnet pools for global scope networks.
         default_addr_pool => "DefaultAddrPool"
     );
 
@@ -73,7 +77,7 @@ impl SwarmInitOptsBuilder {
     );
 
     impl_field!(
-        // SubnetSize specifies the subnet size of the networks created from the default subnet pool.
+        /// SubnetSize specifies the subnet size of the networks created from the default subnet pool.
         subnet_size: u32 => "SubnetSize"
     );
 
@@ -82,3 +86,111 @@ impl SwarmInitOptsBuilder {
         spec: SwarmSpec => "Spec"
     );
 }
+
+/// Options for updating a swarm with [`Swarm::update`](crate::Swarm::update).
+#[derive(Debug, Clone)]
+pub struct SwarmUpdateOpts {
+    spec: SwarmSpec,
+    params: HashMap<&'static str, String>,
+}
+
+impl SwarmUpdateOpts {
+    /// Returns a builder for updating the swarm to `spec`. The `version` must be the current
+    /// version of the swarm object as returned by [`Swarm::inspect`](crate::Swarm::inspect),
+    /// it is used to avoid conflicting writes.
+    pub fn builder(version: u64, spec: SwarmSpec) -> SwarmUpdateOptsBuilder {
+        SwarmUpdateOptsBuilder::new(version, spec)
+    }
+
+    /// Serializes the swarm specification as a JSON string.
+    pub fn serialize(&self) -> Result<String> {
+        serde_json::to_string(&self.spec).map_err(Error::from)
+    }
+
+    pub(crate) fn query(&self) -> String {
+        containers_api::url::encoded_pairs(&self.params)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SwarmUpdateOptsBuilder {
+    spec: SwarmSpec,
+    params: HashMap<&'static str, String>,
+}
+
+impl SwarmUpdateOptsBuilder {
+    pub fn new(version: u64, spec: SwarmSpec) -> Self {
+        Self {
+            spec,
+            params: HashMap::from([("version", version.to_string())]),
+        }
+    }
+
+    impl_url_bool_field!(
+        /// Rotate the worker join token.
+        rotate_worker_token => "rotateWorkerToken"
+    );
+
+    impl_url_bool_field!(
+        /// Rotate the manager join token.
+        rotate_manager_token => "rotateManagerToken"
+    );
+
+    impl_url_bool_field!(
+        /// Rotate the manager unlock key.
+        rotate_manager_unlock_key => "rotateManagerUnlockKey"
+    );
+
+    pub fn build(self) -> SwarmUpdateOpts {
+        SwarmUpdateOpts {
+            spec: self.spec,
+            params: self.params,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn swarm_init_opts() {
+        let opts = SwarmInitOpts::builder()
+            .listen_addr("0.0.0.0:2377")
+            .advertise_addr("192.168.1.1:2377")
+            .force_new_cluster(true)
+            .build();
+
+        let body: Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "ListenAddr": "0.0.0.0:2377",
+                "AdvertiseAddr": "192.168.1.1:2377",
+                "ForceNewCluster": true,
+            })
+        );
+    }
+
+    #[test]
+    fn swarm_update_opts() {
+        let spec: SwarmSpec = serde_json::from_value(json!({"Name": "default"})).unwrap();
+        let opts = SwarmUpdateOpts::builder(42, spec)
+            .rotate_worker_token(true)
+            .build();
+
+        let pairs: HashMap<_, _> = url::form_urlencoded::parse(opts.query().as_bytes())
+            .into_owned()
+            .collect();
+        assert_eq!(
+            pairs,
+            HashMap::from([
+                ("version".to_string(), "42".to_string()),
+                ("rotateWorkerToken".to_string(), "true".to_string()),
+            ])
+        );
+        let body: Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(body["Name"], "default");
+    }
+}
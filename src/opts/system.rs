@@ -1,3 +1,5 @@
+use crate::opts::{Filters, Timestamp};
+
 use std::collections::HashMap;
 
 /// Opts for filtering streams of Docker events
@@ -59,96 +61,46 @@ pub enum EventFilter {
 /// Builder interface for [`EventOpts`](EventOpts).
 pub struct EventsOptsBuilder {
     params: HashMap<&'static str, String>,
-    events: Vec<String>,
-    containers: Vec<String>,
-    images: Vec<String>,
-    labels: Vec<String>,
-    volumes: Vec<String>,
-    networks: Vec<String>,
-    daemons: Vec<String>,
-    types: Vec<String>,
+    filters: Filters,
 }
 
 impl EventsOptsBuilder {
-    #[cfg(feature = "chrono")]
-    /// Only return events since this time.
-    pub fn since<Tz>(mut self, timestamp: &chrono::DateTime<Tz>) -> Self
+    /// Only return events since this time, for example a UNIX timestamp in seconds or a
+    /// [`SystemTime`](std::time::SystemTime).
+    pub fn since<T>(mut self, timestamp: T) -> Self
     where
-        Tz: chrono::TimeZone,
+        T: Into<Timestamp>,
     {
-        self.params
-            .insert("since", timestamp.timestamp().to_string());
-        self
-    }
-
-    #[cfg(not(feature = "chrono"))]
-    /// Only return events since this time, as a UNIX timestamp.
-    pub fn since(mut self, timestamp: i64) -> Self {
-        self.params.insert("since", timestamp.to_string());
+        self.params.insert("since", timestamp.into().to_string());
         self
     }
 
-    #[cfg(feature = "chrono")]
-    /// Only return events before this time.
-    pub fn until<Tz>(mut self, timestamp: &chrono::DateTime<Tz>) -> Self
+    /// Only return events before this time, for example a UNIX timestamp in seconds or a
+    /// [`SystemTime`](std::time::SystemTime).
+    pub fn until<T>(mut self, timestamp: T) -> Self
     where
-        Tz: chrono::TimeZone,
+        T: Into<Timestamp>,
     {
-        self.params
-            .insert("until", timestamp.timestamp().to_string());
+        self.params.insert("until", timestamp.into().to_string());
         self
     }
 
-    #[cfg(not(feature = "chrono"))]
-    /// Only return events before this time, as a UNIX timestamp.
-    pub fn until(mut self, timestamp: i64) -> Self {
-        self.params.insert("until", timestamp.to_string());
-        self
-    }
-
-    /// Filter the events by a list of event filters.
+    /// Filter the events by a list of event filters. Filters from multiple calls are combined.
     pub fn filter(mut self, filters: Vec<EventFilter>) -> Self {
-        let mut params = HashMap::new();
         for f in filters {
             match f {
-                EventFilter::Container(n) => {
-                    self.containers.push(n);
-                    params.insert("container", self.containers.clone())
-                }
-                EventFilter::Event(n) => {
-                    self.events.push(n);
-                    params.insert("event", self.events.clone())
-                }
-                EventFilter::Image(n) => {
-                    self.images.push(n);
-                    params.insert("image", self.images.clone())
-                }
-                EventFilter::Label(n) => {
-                    self.labels.push(n);
-                    params.insert("label", self.labels.clone())
-                }
-                EventFilter::Volume(n) => {
-                    self.volumes.push(n);
-                    params.insert("volume", self.volumes.clone())
-                }
-                EventFilter::Network(n) => {
-                    self.networks.push(n);
-                    params.insert("network", self.networks.clone())
-                }
-                EventFilter::Daemon(n) => {
-                    self.daemons.push(n);
-                    params.insert("daemon", self.daemons.clone())
-                }
-                EventFilter::Type(n) => {
-                    self.types.push(n.as_ref().to_string());
-                    params.insert("type", self.types.clone())
-                }
+                EventFilter::Container(n) => self.filters.add("container", n),
+                EventFilter::Event(n) => self.filters.add("event", n),
+                EventFilter::Image(n) => self.filters.add("image", n),
+                EventFilter::Label(n) => self.filters.add("label", n),
+                EventFilter::Volume(n) => self.filters.add("volume", n),
+                EventFilter::Network(n) => self.filters.add("network", n),
+                EventFilter::Daemon(n) => self.filters.add("daemon", n),
+                EventFilter::Type(n) => self.filters.add("type", n.as_ref()),
             };
         }
-        self.params.insert(
-            "filters",
-            serde_json::to_string(&params).unwrap_or_default(),
-        );
+
+        self.params.insert("filters", self.filters.to_json());
         self
     }
 
@@ -159,3 +111,31 @@ impl EventsOptsBuilder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_opts_filters() {
+        let opts = EventsOpts::builder()
+            .filter(vec![EventFilter::Type(EventFilterType::Container)])
+            .filter(vec![EventFilter::Event("start".into())])
+            .build();
+
+        let query = opts.serialize().unwrap();
+        let (key, filters) = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .next()
+            .unwrap();
+        assert_eq!(key, "filters");
+        let filters: HashMap<String, Vec<String>> = serde_json::from_str(&filters).unwrap();
+        assert_eq!(
+            filters,
+            HashMap::from([
+                ("type".to_string(), vec!["container".to_string()]),
+                ("event".to_string(), vec!["start".to_string()]),
+            ])
+        );
+    }
+}
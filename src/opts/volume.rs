@@ -23,6 +23,8 @@ impl VolumeCreateOptsBuilder {
         /// User-defined key/value metadata.
         labels => "Labels"
     );
+
+    impl_add_label!(VolumeCreateOptsBuilder);
 }
 
 impl_opts_builder!(url => VolumePrune);
@@ -70,3 +72,43 @@ impl VolumeListOptsBuilder {
         VolumeFilter
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volume_create_add_label() {
+        let opts = VolumeCreateOpts::builder()
+            .add_label("app", "test")
+            .add_label("env", "prod")
+            .build();
+
+        let body: serde_json::Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(
+            body["Labels"],
+            serde_json::json!({"app": "test", "env": "prod"})
+        );
+    }
+
+    #[test]
+    fn volume_create_opts() {
+        let opts = VolumeCreateOpts::builder()
+            .name("test-volume")
+            .driver("local")
+            .driver_opts([("type", "tmpfs")])
+            .labels([("app", "test")])
+            .build();
+
+        let body: serde_json::Value = serde_json::from_str(&opts.serialize().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "Name": "test-volume",
+                "Driver": "local",
+                "DriverOpts": {"type": "tmpfs"},
+                "Labels": {"app": "test"},
+            })
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! Construction of request paths.
+
+/// Builder of request paths like `/networks?filters=...` from a path and optional, already
+/// encoded, query strings. Queries that are missing or empty are skipped so that the built path
+/// never ends with a dangling `?` or `&`.
+///
+/// Paths are built without the API version prefix like `/v1.41`, it is added by
+/// [`Docker`](crate::Docker) when sending the request.
+#[derive(Debug, Clone)]
+pub(crate) struct PathBuilder {
+    path: String,
+    query: Vec<String>,
+}
+
+impl PathBuilder {
+    pub(crate) fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            query: Vec::new(),
+        }
+    }
+
+    /// Appends an encoded query string like `all=true&limit=5`.
+    pub(crate) fn query<Q: AsRef<str>>(mut self, query: Option<Q>) -> Self {
+        if let Some(query) = query {
+            let query = query.as_ref().trim_matches('&');
+            if !query.is_empty() {
+                self.query.push(query.to_owned());
+            }
+        }
+        self
+    }
+
+    pub(crate) fn build(self) -> String {
+        if self.query.is_empty() {
+            self.path
+        } else {
+            format!("{}?{}", self.path, self.query.join("&"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_paths() {
+        assert_eq!(PathBuilder::new("/networks").build(), "/networks");
+        assert_eq!(
+            PathBuilder::new("/networks").query(None::<String>).build(),
+            "/networks"
+        );
+        assert_eq!(
+            PathBuilder::new("/networks").query(Some("")).build(),
+            "/networks"
+        );
+        assert_eq!(
+            PathBuilder::new("/networks")
+                .query(Some("filters=%7B%7D"))
+                .build(),
+            "/networks?filters=%7B%7D"
+        );
+        assert_eq!(
+            PathBuilder::new("/containers/json")
+                .query(Some("all=true"))
+                .query(Some(""))
+                .query(Some("limit=5&"))
+                .build(),
+            "/containers/json?all=true&limit=5"
+        );
+    }
+}
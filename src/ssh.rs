@@ -0,0 +1,245 @@
+//! Connections to docker hosts over SSH by forwarding the remote docker socket with `ssh -L`.
+use crate::{Error, Result};
+
+use std::{
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tempfile::TempDir;
+
+/// Path of the docker socket on the remote host if the URL doesn't contain one.
+const DEFAULT_REMOTE_SOCKET: &str = "/var/run/docker.sock";
+/// How long to wait for `ssh` to create the forwarded socket.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The parts of a URL like `ssh://user@host:2222/run/docker.sock`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SshUrl {
+    pub(crate) user: Option<String>,
+    pub(crate) host: String,
+    pub(crate) port: Option<u16>,
+    /// Path of the docker socket on the remote host.
+    pub(crate) socket: String,
+}
+
+impl SshUrl {
+    pub(crate) fn parse(uri: &str) -> Result<SshUrl> {
+        let url = url::Url::parse(uri).map_err(Error::InvalidUrl)?;
+        if url.scheme() != "ssh" {
+            return Err(Error::UnsupportedScheme(url.scheme().to_owned()));
+        }
+        let host = url
+            .host_str()
+            .filter(|host| !host.is_empty())
+            .ok_or(Error::MissingAuthority)?;
+        let socket = match url.path() {
+            "" | "/" => DEFAULT_REMOTE_SOCKET,
+            path => path,
+        };
+
+        Ok(SshUrl {
+            user: Some(url.username())
+                .filter(|user| !user.is_empty())
+                .map(ToOwned::to_owned),
+            host: host.to_owned(),
+            port: url.port(),
+            socket: socket.to_owned(),
+        })
+    }
+
+    /// Arguments of `ssh` forwarding the remote docker socket to `local_socket`.
+    pub(crate) fn forward_args(&self, local_socket: &Path) -> Vec<String> {
+        let mut args = vec![
+            "-nNT".to_owned(),
+            "-o".to_owned(),
+            "ExitOnForwardFailure=yes".to_owned(),
+            "-o".to_owned(),
+            "BatchMode=yes".to_owned(),
+            "-L".to_owned(),
+            format!("{}:{}", local_socket.display(), self.socket),
+        ];
+        if let Some(port) = self.port {
+            args.extend(["-p".to_owned(), port.to_string()]);
+        }
+        if let Some(user) = &self.user {
+            args.extend(["-l".to_owned(), user.clone()]);
+        }
+        args.extend(["--".to_owned(), self.host.clone()]);
+        args
+    }
+}
+
+/// A running `ssh` process forwarding the remote docker socket to a local Unix socket. The
+/// process is killed and the local socket removed on drop.
+#[derive(Debug)]
+pub(crate) struct SshTunnel {
+    child: Mutex<Child>,
+    local_socket: PathBuf,
+    /// Directory only accessible by the current user containing `local_socket`, so that no
+    /// other user can create the socket in place of `ssh`.
+    _socket_dir: TempDir,
+}
+
+impl SshTunnel {
+    /// Starts `ssh` and waits until the forwarded socket is created. Authentication must not
+    /// require any user input, for example by using an SSH agent.
+    pub(crate) async fn open(url: &SshUrl) -> Result<SshTunnel> {
+        let socket_dir = private_dir()?;
+        let local_socket = socket_dir.path().join("docker.sock");
+        if local_socket.exists() {
+            return Err(Error::StringError(format!(
+                "local socket `{}` of the ssh tunnel already exists",
+                local_socket.display()
+            )));
+        }
+        let mut child = Command::new("ssh")
+            .args(url.forward_args(&local_socket))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| Error::StringError(format!("failed to start ssh - {}", e)))?;
+
+        let started = Instant::now();
+        while !local_socket.exists() {
+            if let Some(status) = child.try_wait()? {
+                let mut stderr = String::new();
+                if let Some(mut pipe) = child.stderr.take() {
+                    let _ = pipe.read_to_string(&mut stderr);
+                }
+                return Err(Error::StringError(format!(
+                    "ssh tunnel to `{}` exited with {} - {}",
+                    url.host,
+                    status,
+                    stderr.trim()
+                )));
+            }
+            if started.elapsed() > CONNECT_TIMEOUT {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Error::StringError(format!(
+                    "ssh tunnel to `{}` was not established within {:?}",
+                    url.host, CONNECT_TIMEOUT
+                )));
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+
+        // keep reading the output so that `ssh` doesn't stall once the pipe is full
+        if let Some(stderr) = child.stderr.take() {
+            std::thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(|line| line.ok()) {
+                    log::debug!("ssh - {}", line);
+                }
+            });
+        }
+
+        Ok(SshTunnel {
+            child: Mutex::new(child),
+            local_socket,
+            _socket_dir: socket_dir,
+        })
+    }
+
+    pub(crate) fn local_socket(&self) -> &Path {
+        &self.local_socket
+    }
+}
+
+/// Creates a temporary directory only accessible by the current user.
+fn private_dir() -> Result<TempDir> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::Builder::new()
+        .prefix("docker-api-ssh-")
+        .tempdir()?;
+    std::fs::set_permissions(dir.path(), std::fs::Permissions::from_mode(0o700))?;
+    Ok(dir)
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        if let Ok(child) = self.child.get_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ssh_urls() {
+        assert_eq!(
+            SshUrl::parse("ssh://alice@docker.example.com").unwrap(),
+            SshUrl {
+                user: Some("alice".into()),
+                host: "docker.example.com".into(),
+                port: None,
+                socket: "/var/run/docker.sock".into(),
+            }
+        );
+        assert_eq!(
+            SshUrl::parse("ssh://10.0.0.5:2222/run/user/1000/docker.sock").unwrap(),
+            SshUrl {
+                user: None,
+                host: "10.0.0.5".into(),
+                port: Some(2222),
+                socket: "/run/user/1000/docker.sock".into(),
+            }
+        );
+        assert!(matches!(
+            SshUrl::parse("ssh://"),
+            Err(Error::MissingAuthority) | Err(Error::InvalidUrl(_))
+        ));
+        assert!(matches!(
+            SshUrl::parse("tcp://localhost:2375"),
+            Err(Error::UnsupportedScheme(scheme)) if scheme == "tcp"
+        ));
+    }
+
+    #[test]
+    fn private_socket_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = private_dir().unwrap();
+        let mode = std::fs::metadata(dir.path()).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        assert!(std::fs::read_dir(dir.path()).unwrap().next().is_none());
+    }
+
+    #[test]
+    fn ssh_hosts_require_async_constructor() {
+        assert!(crate::Docker::new("ssh://docker.example.com").is_err());
+    }
+
+    #[test]
+    fn forward_args() {
+        let url = SshUrl::parse("ssh://alice@docker.example.com:2222").unwrap();
+        assert_eq!(
+            url.forward_args(Path::new("/tmp/docker.sock")),
+            vec![
+                "-nNT",
+                "-o",
+                "ExitOnForwardFailure=yes",
+                "-o",
+                "BatchMode=yes",
+                "-L",
+                "/tmp/docker.sock:/var/run/docker.sock",
+                "-p",
+                "2222",
+                "-l",
+                "alice",
+                "--",
+                "docker.example.com",
+            ]
+        );
+    }
+}
@@ -0,0 +1,408 @@
+//! Decoding of streamed responses.
+use crate::{conn::TtyChunk, Error, Result};
+
+use futures_util::{ready, Stream};
+use hyper::body::Bytes;
+use serde::de::DeserializeOwned;
+
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Stream adapter decoding newline delimited JSON documents, as returned by endpoints like
+/// events, image pulls or builds, from a stream of body chunks. A document split across multiple
+/// chunks is buffered until the terminating newline or the end of the body is received.
+pub(crate) struct JsonLineDecoder<S, T> {
+    inner: S,
+    buf: Vec<u8>,
+    done: bool,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<S, T> JsonLineDecoder<S, T> {
+    pub(crate) fn new(inner: S) -> Self {
+        Self {
+            inner,
+            buf: Vec::new(),
+            done: false,
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<S, T> Stream for JsonLineDecoder<S, T>
+where
+    S: Stream<Item = Result<Bytes>> + Unpin,
+    T: DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(end) = this.buf.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = this.buf.drain(..=end).collect();
+                match decode_line(&line) {
+                    Some(item) => return Poll::Ready(Some(item)),
+                    None => continue,
+                }
+            }
+
+            if this.done {
+                let rest = std::mem::take(&mut this.buf);
+                return Poll::Ready(decode_line(&rest));
+            }
+
+            match ready!(Pin::new(&mut this.inner).poll_next(cx)) {
+                Some(Ok(chunk)) => this.buf.extend_from_slice(&chunk),
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => this.done = true,
+            }
+        }
+    }
+}
+
+/// Decodes a single line, returns `None` if the line is blank.
+fn decode_line<T: DeserializeOwned>(line: &[u8]) -> Option<Result<T>> {
+    if line.iter().all(u8::is_ascii_whitespace) {
+        None
+    } else {
+        Some(serde_json::from_slice(line).map_err(Error::from))
+    }
+}
+
+/// Splits log chunks of a container, requested with `timestamps` set, into lines and keeps track
+/// of the time of the last returned line so that the logs can be requested again from that
+/// point without returning lines twice.
+#[derive(Debug, Default)]
+pub(crate) struct LogLines {
+    frames: Vec<u8>,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    lines: VecDeque<TtyChunk>,
+    /// Time of the last returned line and the number of returned lines with that time.
+    last: Option<((i64, u32), usize)>,
+    /// Number of lines with the time of the last returned line still to skip.
+    skip: usize,
+}
+
+impl LogLines {
+    /// Adds a chunk of logs, complete lines can be taken with [`LogLines::next_line`].
+    pub(crate) fn push(&mut self, chunk: TtyChunk) {
+        let (mut buf, bytes, is_stderr) = match chunk {
+            TtyChunk::StdErr(bytes) => (std::mem::take(&mut self.stderr), bytes, true),
+            TtyChunk::StdOut(bytes) | TtyChunk::StdIn(bytes) => {
+                (std::mem::take(&mut self.stdout), bytes, false)
+            }
+        };
+        buf.extend_from_slice(&bytes);
+        while let Some(end) = buf.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buf.drain(..=end).collect();
+            self.push_line(line, is_stderr);
+        }
+
+        if is_stderr {
+            self.stderr = buf;
+        } else {
+            self.stdout = buf;
+        }
+    }
+
+    /// Adds a chunk of the multiplexed logs of a container created without a TTY. Frames split
+    /// across chunks are buffered.
+    pub(crate) fn push_multiplexed(&mut self, bytes: &[u8]) {
+        self.frames.extend_from_slice(bytes);
+        while self.frames.len() >= 8 {
+            let len = u32::from_be_bytes([
+                self.frames[4],
+                self.frames[5],
+                self.frames[6],
+                self.frames[7],
+            ]) as usize;
+            if self.frames.len() < 8 + len {
+                break;
+            }
+            let frame: Vec<u8> = self.frames.drain(..8 + len).collect();
+            let data = frame[8..].to_vec();
+            self.push(match frame[0] {
+                2 => TtyChunk::StdErr(data),
+                _ => TtyChunk::StdOut(data),
+            });
+        }
+    }
+
+    fn push_line(&mut self, line: Vec<u8>, is_stderr: bool) {
+        if let Some(time) = log_line_time(&line) {
+            match &mut self.last {
+                Some((last, count)) if time == *last => {
+                    if self.skip > 0 {
+                        self.skip -= 1;
+                        return;
+                    }
+                    *count += 1;
+                }
+                Some((last, _)) if time < *last => return,
+                _ => {
+                    self.last = Some((time, 1));
+                    self.skip = 0;
+                }
+            }
+        }
+
+        self.lines.push_back(if is_stderr {
+            TtyChunk::StdErr(line)
+        } else {
+            TtyChunk::StdOut(line)
+        });
+    }
+
+    /// Returns the next complete line.
+    pub(crate) fn next_line(&mut self) -> Option<TtyChunk> {
+        self.lines.pop_front()
+    }
+
+    /// Adds incomplete lines left at the end of the logs.
+    pub(crate) fn finish(&mut self) {
+        for (line, is_stderr) in [
+            (std::mem::take(&mut self.stdout), false),
+            (std::mem::take(&mut self.stderr), true),
+        ] {
+            if !line.is_empty() {
+                self.push_line(line, is_stderr);
+            }
+        }
+    }
+
+    /// Prepares for receiving the logs again starting at [`LogLines::since`]. Incomplete lines
+    /// are dropped as they are sent again and lines that were already returned are skipped.
+    pub(crate) fn restart(&mut self) {
+        self.frames.clear();
+        self.stdout.clear();
+        self.stderr.clear();
+        self.skip = self.last.map(|(_, count)| count).unwrap_or_default();
+    }
+
+    /// Returns the time of the last returned line as the `since` parameter of the logs endpoint.
+    pub(crate) fn since(&self) -> Option<String> {
+        self.last
+            .map(|((secs, nanos), _)| format!("{}.{:09}", secs, nanos))
+    }
+}
+
+/// Returns the time a log line starts with.
+fn log_line_time(line: &[u8]) -> Option<(i64, u32)> {
+    let end = line.iter().position(|b| *b == b' ')?;
+    parse_log_time(std::str::from_utf8(&line[..end]).ok()?)
+}
+
+/// Parses a time like `2023-01-02T15:04:05.999999999Z`, as added to log lines by the daemon, into
+/// seconds and nanoseconds since the UNIX epoch.
+fn parse_log_time(time: &str) -> Option<(i64, u32)> {
+    let (date, time) = time.strip_suffix('Z')?.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let mut time = time.splitn(3, ':').map(str::parse::<i64>);
+    let (hour, min, sec) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+    if !(1..=12).contains(&month)
+        || fraction.len() > 9
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let nanos = format!("{:0<9}", fraction).parse().ok()?;
+
+    // days since the epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let year = if month <= 2 { year - 1 } else { year };
+    let (era, year_of_era) = (year.div_euclid(400), year.rem_euclid(400));
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let days = era * 146_097 + year_of_era * 365 + year_of_era / 4 - year_of_era / 100
+        + day_of_year
+        - 719_468;
+
+    Some((days * 86_400 + hour * 3_600 + min * 60 + sec, nanos))
+}
+
+/// Adapter implementing the `futures` IO traits for a connection implementing the `tokio` ones,
+/// like the upgraded connection of an attach request.
+pub(crate) struct Compat<S>(S);
+
+impl<S> Compat<S> {
+    pub(crate) fn new(inner: S) -> Self {
+        Self(inner)
+    }
+}
+
+impl<S> futures_util::io::AsyncRead for Compat<S>
+where
+    S: tokio::io::AsyncRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let mut buf = tokio::io::ReadBuf::new(buf);
+        ready!(Pin::new(&mut self.0).poll_read(cx, &mut buf))?;
+        Poll::Ready(Ok(buf.filled().len()))
+    }
+}
+
+impl<S> futures_util::io::AsyncWrite for Compat<S>
+where
+    S: tokio::io::AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{stream, StreamExt};
+
+    fn chunks(chunks: &[&'static str]) -> impl Stream<Item = Result<Bytes>> + Unpin {
+        stream::iter(
+            chunks
+                .iter()
+                .map(|chunk| Ok(Bytes::from_static(chunk.as_bytes())))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[tokio::test]
+    async fn decodes_document_split_across_chunks() {
+        let decoder = JsonLineDecoder::new(chunks(&[
+            r#"{"status":"Pulling fs layer","#,
+            r#""id":"abc"}"#,
+            "\r\n",
+        ]));
+        let items: Vec<Result<serde_json::Value>> = decoder.collect().await;
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(
+            items[0].as_ref().unwrap(),
+            &serde_json::json!({"status": "Pulling fs layer", "id": "abc"})
+        );
+    }
+
+    #[tokio::test]
+    async fn decodes_multiple_documents_per_chunk() {
+        let decoder = JsonLineDecoder::new(chunks(&["{\"n\":1}\n\n{\"n\":2}\n{\"n\"", ":3}"]));
+        let items: Vec<serde_json::Value> = decoder.map(Result::unwrap).collect().await;
+
+        assert_eq!(
+            items,
+            vec![
+                serde_json::json!({"n": 1}),
+                serde_json::json!({"n": 2}),
+                serde_json::json!({"n": 3})
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_invalid_documents() {
+        let decoder = JsonLineDecoder::new(chunks(&["{\"n\":1}\nnot json\n{\"n\":2}\n"]));
+        let items: Vec<Result<serde_json::Value>> = decoder.collect().await;
+
+        assert_eq!(items.len(), 3);
+        assert!(items[0].is_ok());
+        assert!(matches!(items[1], Err(Error::SerdeJsonError(_))));
+        assert!(items[2].is_ok());
+    }
+
+    #[test]
+    fn parses_log_times() {
+        assert_eq!(parse_log_time("1970-01-01T00:00:00Z"), Some((0, 0)));
+        assert_eq!(
+            parse_log_time("2024-01-01T00:00:00.000000002Z"),
+            Some((1_704_067_200, 2))
+        );
+        assert_eq!(
+            parse_log_time("2023-03-01T12:30:45.5Z"),
+            Some((1_677_673_845, 500_000_000))
+        );
+        assert_eq!(parse_log_time("1969-12-31T23:59:59Z"), Some((-1, 0)));
+        assert_eq!(parse_log_time("2023-03-01T12:30:45+01:00"), None);
+        assert_eq!(parse_log_time("2023-13-01T12:30:45Z"), None);
+        assert_eq!(parse_log_time("hello"), None);
+    }
+
+    fn stdout(line: &str) -> TtyChunk {
+        TtyChunk::StdOut(line.as_bytes().to_vec())
+    }
+
+    fn lines(log: &mut LogLines) -> Vec<String> {
+        std::iter::from_fn(|| log.next_line())
+            .map(|line| String::from_utf8(line.into()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn splits_log_lines() {
+        let mut log = LogLines::default();
+        log.push(stdout("2024-01-01T00:00:01Z one\n2024-01-01T00:00:02Z t"));
+        log.push(TtyChunk::StdErr(b"2024-01-01T00:00:02Z err\n".to_vec()));
+        log.push(stdout("wo\n2024-01-01T00:00:03Z three"));
+        assert!(matches!(log.next_line(), Some(TtyChunk::StdOut(_))));
+        assert!(matches!(log.next_line(), Some(TtyChunk::StdErr(_))));
+        assert_eq!(lines(&mut log), vec!["2024-01-01T00:00:02Z two\n"]);
+
+        log.finish();
+        assert_eq!(lines(&mut log), vec!["2024-01-01T00:00:03Z three"]);
+        assert_eq!(log.since().as_deref(), Some("1704067203.000000000"));
+    }
+
+    #[test]
+    fn splits_multiplexed_log_frames() {
+        let mut log = LogLines::default();
+        log.push_multiplexed(b"\x02\0\0\0\0\0\0\x192024-01-01T00:00:01Z e");
+        log.push_multiplexed(b"rr\n\x01\0\0\0\0\0\0\x192024-01-01T00:00:02Z");
+        assert!(matches!(log.next_line(), Some(TtyChunk::StdErr(_))));
+        assert!(log.next_line().is_none());
+
+        log.push_multiplexed(b" out\n");
+        assert_eq!(lines(&mut log), vec!["2024-01-01T00:00:02Z out\n"]);
+    }
+
+    #[test]
+    fn skips_returned_log_lines_after_restart() {
+        let mut log = LogLines::default();
+        log.push(stdout(
+            "2024-01-01T00:00:01Z one\n2024-01-01T00:00:02Z two\n2024-01-01T00:00:02Z also two\n",
+        ));
+        log.push(stdout("2024-01-01T00:00:03Z incomplete"));
+        assert_eq!(lines(&mut log).len(), 3);
+        assert_eq!(log.since().as_deref(), Some("1704067202.000000000"));
+
+        log.restart();
+        log.push(stdout(
+            "2024-01-01T00:00:02Z two\n2024-01-01T00:00:02Z also two\n\
+             2024-01-01T00:00:02Z still two\n2024-01-01T00:00:03Z three\n",
+        ));
+        assert_eq!(
+            lines(&mut log),
+            vec![
+                "2024-01-01T00:00:02Z still two\n",
+                "2024-01-01T00:00:03Z three\n"
+            ]
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Helpers for testing requests sent by the client without a running docker daemon.
+//!
+//! Enable the `test-util` feature to use them in tests of crates depending on this one.
+//!
+//! ```ignore
+//! # async {
+//! use docker_api::{opts::NetworkCreateOpts, testutil::MockServer};
+//!
+//! let server = MockServer::start("201 Created", r#"{"Id":"22be93d5babb"}"#).await;
+//! let opts = NetworkCreateOpts::builder("test").build().unwrap();
+//! server.docker().networks().create(&opts).await.unwrap();
+//!
+//! let request = &server.recorded_requests()[0];
+//! assert_eq!(request.method, "POST");
+//! assert_eq!(request.path, "/v1.41/networks/create");
+//! # };
+//! ```
+use crate::Docker;
+
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+/// A request received by a [`MockServer`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Path of the request including the API version prefix and the query.
+    pub path: String,
+    /// Headers in the order they were received with their names in lowercase.
+    pub headers: Vec<(String, String)>,
+    /// Body of the request, chunked bodies are decoded.
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    /// Returns the value of the first header with the given name, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Parses the body as JSON.
+    pub fn body_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::from_slice(&self.body)
+    }
+}
+
+/// A local HTTP server answering requests with canned responses. All received requests are
+/// recorded.
+pub struct MockServer {
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+/// How a [`MockServer`] answers the requests it receives.
+enum Responses {
+    /// Raw responses written to consecutive connections, the last one is repeated.
+    Sequence(Vec<Vec<u8>>),
+    /// Connections are kept open without ever writing a response.
+    Never,
+}
+
+impl MockServer {
+    /// Starts a server responding with `status`, for example `204 No Content`, and `body`.
+    pub async fn start(status: impl Into<String>, body: impl Into<String>) -> Self {
+        Self::start_sequence([(status, body)]).await
+    }
+
+    /// Starts a server responding to consecutive requests with the given status and body pairs.
+    /// The last response is repeated once all others were sent.
+    pub async fn start_sequence<I, S, B>(responses: I) -> Self
+    where
+        I: IntoIterator<Item = (S, B)>,
+        S: Into<String>,
+        B: Into<String>,
+    {
+        Self::start_raw_sequence(responses.into_iter().map(|(status, body)| {
+            let body = body.into();
+            format!(
+                "HTTP/1.1 {}\r\nconnection: close\r\ncontent-length: {}\r\n\r\n{}",
+                status.into(),
+                body.len(),
+                body
+            )
+        }))
+        .await
+    }
+
+    /// Starts a server writing the given raw responses, including the status line and headers,
+    /// to consecutive connections before closing them. Allows sending truncated or malformed
+    /// responses, an empty response closes the connection without responding. The last response
+    /// is repeated once all others were sent.
+    pub async fn start_raw_sequence<I, R>(responses: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: Into<Vec<u8>>,
+    {
+        let responses: Vec<Vec<u8>> = responses.into_iter().map(Into::into).collect();
+        assert!(!responses.is_empty(), "at least one response is required");
+        Self::spawn(Responses::Sequence(responses)).await
+    }
+
+    /// Starts a server that records requests but never responds to them, for example to test
+    /// timeouts. The server never closes the connections.
+    pub async fn start_unresponsive() -> Self {
+        Self::spawn(Responses::Never).await
+    }
+
+    async fn spawn(responses: Responses) -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+
+        tokio::spawn(async move {
+            let mut stalled = Vec::new();
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let request = read_request(&mut socket).await;
+                let count = {
+                    let mut received = received.lock().unwrap();
+                    received.push(request);
+                    received.len()
+                };
+                match &responses {
+                    Responses::Sequence(responses) => {
+                        let response = &responses[(count - 1).min(responses.len() - 1)];
+                        let _ = socket.write_all(response).await;
+                    }
+                    Responses::Never => stalled.push(socket),
+                }
+            }
+        });
+
+        Self { addr, requests }
+    }
+
+    /// Returns the `tcp://` URI of this server, for example to configure a client with
+    /// [`Docker::builder`].
+    pub fn uri(&self) -> String {
+        format!("tcp://{}", self.addr)
+    }
+
+    /// Returns a client connected to this server.
+    pub fn docker(&self) -> Docker {
+        Docker::new(self.uri()).unwrap()
+    }
+
+    /// Returns the method and path of all requests received so far.
+    pub fn requests(&self) -> Vec<(String, String)> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|request| (request.method.clone(), request.path.clone()))
+            .collect()
+    }
+
+    /// Returns all requests received so far including their headers and bodies.
+    pub fn recorded_requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Reads a single request from `socket`. Whatever was received is recorded if the connection
+/// is closed early or the request is malformed.
+async fn read_request(socket: &mut TcpStream) -> RecordedRequest {
+    let mut buf = Vec::new();
+    let mut chunk = [0; 4096];
+    let mut request = RecordedRequest::default();
+    let mut head_len = None;
+
+    loop {
+        if head_len.is_none() {
+            head_len = buf
+                .windows(4)
+                .position(|window| window == b"\r\n\r\n")
+                .map(|end| end + 4);
+            if let Some(head_len) = head_len {
+                request = parse_head(&buf[..head_len]);
+            }
+        }
+        if let Some(head_len) = head_len {
+            if let Some(body) = decode_body(&request, &buf[head_len..]) {
+                request.body = body;
+                return request;
+            }
+        }
+
+        match socket.read(&mut chunk).await {
+            Ok(0) | Err(_) => {
+                if let Some(head_len) = head_len {
+                    request.body = buf[head_len..].to_vec();
+                }
+                return request;
+            }
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+        }
+    }
+}
+
+fn parse_head(head: &[u8]) -> RecordedRequest {
+    let head = String::from_utf8_lossy(head);
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+
+    RecordedRequest {
+        method: request_line.next().unwrap_or_default().to_owned(),
+        path: request_line.next().unwrap_or_default().to_owned(),
+        headers: lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| (key.trim().to_ascii_lowercase(), value.trim().to_owned()))
+            .collect(),
+        body: Vec::new(),
+    }
+}
+
+/// Returns the decoded body once it was received completely.
+fn decode_body(request: &RecordedRequest, body: &[u8]) -> Option<Vec<u8>> {
+    if matches!(
+        request.header("transfer-encoding"),
+        Some(encoding) if encoding.eq_ignore_ascii_case("chunked")
+    ) {
+        return decode_chunked(body);
+    }
+
+    let len = request
+        .header("content-length")
+        .and_then(|len| len.parse::<usize>().ok())
+        .unwrap_or_default();
+    (body.len() >= len).then(|| body[..len].to_vec())
+}
+
+/// Decodes a body with chunked transfer encoding, returns `None` if it is incomplete.
+fn decode_chunked(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    loop {
+        let line_end = body.windows(2).position(|window| window == b"\r\n")?;
+        let size = std::str::from_utf8(&body[..line_end]).ok()?;
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        body = &body[line_end + 2..];
+        if body.len() < size + 2 {
+            return None;
+        }
+        if size == 0 {
+            return Some(decoded);
+        }
+        decoded.extend_from_slice(&body[..size]);
+        body = &body[size + 2..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunked_body() {
+        assert_eq!(
+            decode_chunked(b"5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n").unwrap(),
+            b"hello world"
+        );
+        assert!(decode_chunked(b"5\r\nhello\r\n6\r\n wor").is_none());
+        assert!(decode_chunked(b"5\r\nhello\r\n0\r\n").is_none());
+    }
+}
@@ -21,20 +21,7 @@ pub fn init_runtime() -> Docker {
     } else {
         #[cfg(unix)]
         {
-            let uid = nix::unistd::Uid::effective();
-            let docker_dir = PathBuf::from(format!("/run/user/{uid}/docker"));
-            let docker_root_dir = PathBuf::from("/var/run");
-            if docker_dir.exists() {
-                Docker::unix(docker_dir.join("docker.sock"))
-            } else if docker_root_dir.exists() {
-                Docker::unix(docker_root_dir.join("docker.sock"))
-            } else {
-                panic!(
-                    "Docker socket not found. Tried {URI_ENV_VAR} env variable, {} and {}",
-                    docker_dir.display(),
-                    docker_root_dir.display()
-                );
-            }
+            Docker::unix(unix_socket_path())
         }
         #[cfg(not(unix))]
         {
@@ -43,6 +30,25 @@ pub fn init_runtime() -> Docker {
     }
 }
 
+#[cfg(unix)]
+/// Returns the path of the Docker socket, preferring a rootless installation.
+pub fn unix_socket_path() -> PathBuf {
+    let uid = nix::unistd::Uid::effective();
+    let docker_dir = PathBuf::from(format!("/run/user/{uid}/docker"));
+    let docker_root_dir = PathBuf::from("/var/run");
+    if docker_dir.exists() {
+        docker_dir.join("docker.sock")
+    } else if docker_root_dir.exists() {
+        docker_root_dir.join("docker.sock")
+    } else {
+        panic!(
+            "Docker socket not found. Tried {URI_ENV_VAR} env variable, {} and {}",
+            docker_dir.display(),
+            docker_root_dir.display()
+        );
+    }
+}
+
 pub async fn create_base_container(
     docker: &Docker,
     name: &str,
@@ -56,6 +62,7 @@ pub async fn create_base_container(
             .name(name)
             .command(DEFAULT_CMD_ARRAY)
             .build()
+            .unwrap()
     });
     docker
         .containers()
@@ -174,7 +181,7 @@ pub async fn create_base_network(
 ) -> api::Network {
     cleanup_network(docker, name).await;
 
-    let opts = opts.unwrap_or_else(|| opts::NetworkCreateOpts::builder(name).build());
+    let opts = opts.unwrap_or_else(|| opts::NetworkCreateOpts::builder(name).build().unwrap());
     docker
         .networks()
         .create(&opts)
@@ -50,6 +50,38 @@ async fn container_inspect() {
     assert!(inspect_result.is_err());
 }
 
+#[tokio::test]
+async fn container_inspect_running() {
+    let docker = init_runtime();
+
+    let container_name = "test-inspect-running-container";
+    let container = create_base_container(&docker, container_name, None).await;
+    let _ = container.start().await;
+
+    let inspect_data = container
+        .inspect()
+        .await
+        .expect("running container inspect data");
+    assert_eq!(
+        inspect_data.id.as_deref(),
+        Some(
+            get_container_full_id(&docker, container_name)
+                .await
+                .as_str()
+        )
+    );
+    assert!(inspect_data.host_config.is_some());
+    assert!(inspect_data.network_settings.is_some());
+
+    let state = inspect_data.state.expect("container state");
+    assert_eq!(state.running, Some(true));
+    assert_eq!(state.status.as_deref(), Some("running"));
+    assert_eq!(state.exit_code, Some(0));
+    assert!(state.started_at.is_some());
+
+    cleanup_container(&docker, container_name).await;
+}
+
 #[tokio::test]
 async fn container_rename() {
     let docker = init_runtime();
@@ -185,6 +217,7 @@ async fn container_kill() {
 
 #[tokio::test]
 async fn container_stop() {
+    use docker_api::models::StopOutcome;
     let docker = init_runtime();
 
     let container_name = "test-stop-container";
@@ -199,7 +232,7 @@ async fn container_stop() {
     assert!(inspect_data.state.unwrap().running.unwrap());
 
     let stop_result = container.stop(None).await;
-    assert!(stop_result.is_ok());
+    assert!(matches!(stop_result, Ok(StopOutcome::Stopped)));
 
     let inspect_data = container
         .inspect()
@@ -207,6 +240,41 @@ async fn container_stop() {
         .expect("stopped container inspect data");
     assert!(!inspect_data.state.unwrap().running.unwrap());
 
+    let stop_result = container.stop(None).await;
+    assert!(matches!(stop_result, Ok(StopOutcome::AlreadyStopped)));
+
+    cleanup_container(&docker, container_name).await;
+}
+
+#[tokio::test]
+async fn container_wait() {
+    use docker_api::opts::WaitCondition;
+    let docker = init_runtime();
+
+    let container_name = "test-wait-container";
+    let container = create_base_container(
+        &docker,
+        container_name,
+        Some(
+            ContainerCreateOpts::builder()
+                .image(DEFAULT_IMAGE)
+                .name(container_name)
+                .command(["bash", "-c", "exit 3"])
+                .build()
+                .unwrap(),
+        ),
+    )
+    .await;
+
+    let _ = container.start().await;
+
+    let wait_info = container
+        .wait(WaitCondition::NotRunning)
+        .await
+        .expect("container wait info");
+    assert_eq!(wait_info.status_code, 3);
+    assert!(wait_info.error.is_none());
+
     cleanup_container(&docker, container_name).await;
 }
 
@@ -298,6 +366,70 @@ async fn container_exec() {
     cleanup_container(&docker, container_name).await;
 }
 
+#[tokio::test]
+async fn container_create_exec_start_inspect() {
+    let docker = init_runtime();
+
+    let container_name = "test-create-exec-container";
+    let container = create_base_container(&docker, container_name, None).await;
+
+    let _ = container.start().await;
+
+    let exec = container
+        .create_exec(
+            &ExecCreateOpts::builder()
+                .attach_stdout(true)
+                .attach_stderr(true)
+                .command(["echo", "hi"])
+                .build(),
+        )
+        .await
+        .expect("created exec instance");
+
+    let chunks: Vec<_> = exec.start().try_collect().await.expect("exec output");
+    let stdout: Vec<u8> = chunks
+        .into_iter()
+        .filter_map(|chunk| match chunk {
+            TtyChunk::StdOut(bytes) => Some(bytes),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+    assert_eq!(String::from_utf8_lossy(&stdout), "hi\n");
+
+    let exec_info = exec.inspect().await.expect("exec inspect data");
+    assert_eq!(exec_info.exit_code, Some(0));
+    assert_eq!(exec_info.running, Some(false));
+
+    cleanup_container(&docker, container_name).await;
+}
+
+#[tokio::test]
+async fn container_export() {
+    let docker = init_runtime();
+
+    let container_name = "test-export-container";
+    let container = create_base_container(&docker, container_name, None).await;
+
+    let bytes = container
+        .export()
+        .try_concat()
+        .await
+        .expect("exported tarball bytes");
+    // POSIX tar archives contain the `ustar` magic at offset 257 of the first header
+    assert!(bytes.len() > 512);
+    assert_eq!(&bytes[257..262], b"ustar");
+
+    let mut archive = tar::Archive::new(&bytes[..]);
+    assert!(archive
+        .entries()
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .any(|entry| entry.path().unwrap().starts_with("etc")));
+
+    cleanup_container(&docker, container_name).await;
+}
+
 #[tokio::test]
 async fn container_copy_from() {
     let docker = init_runtime();
@@ -368,6 +500,51 @@ async fn container_copy_file_into() {
     cleanup_container(&docker, container_name).await;
 }
 
+#[tokio::test]
+async fn container_copy_to_from_roundtrip() {
+    let docker = init_runtime();
+
+    let container_name = "test-copy-roundtrip-container";
+    let container = create_base_container(&docker, container_name, None).await;
+
+    let _ = container.start().await;
+
+    let data = b"roundtrip data\n";
+    let mut ar = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o0644);
+    ar.append_data(&mut header, "test-roundtrip", &data[..])
+        .unwrap();
+    let tarball = ar.into_inner().unwrap();
+
+    container
+        .copy_to(std::path::Path::new("/tmp"), tarball.into())
+        .await
+        .expect("copied tarball into container");
+
+    let stat = container
+        .stat_path("/tmp/test-roundtrip")
+        .await
+        .expect("path stat");
+    assert_eq!(stat.name, "test-roundtrip");
+    assert_eq!(stat.size, data.len() as i64);
+
+    let bytes = container
+        .copy_from("/tmp/test-roundtrip")
+        .try_concat()
+        .await
+        .expect("joined tarball bytes");
+    let mut archive = tar::Archive::new(&bytes[..]);
+    let mut entries = archive.entries().unwrap();
+    let mut entry = entries.next().expect("tarball entry").unwrap();
+    let mut content = Vec::new();
+    std::io::Read::read_to_end(&mut entry, &mut content).unwrap();
+    assert_eq!(content, data);
+
+    cleanup_container(&docker, container_name).await;
+}
+
 #[tokio::test]
 async fn container_changes() {
     let docker = init_runtime();
@@ -390,23 +567,19 @@ async fn container_changes() {
     );
     while exec_stream.next().await.is_some() {}
 
-    use docker_api::models::ContainerChangeResponseItem;
+    use docker_api::models::{ChangeKind, FilesystemChange};
 
-    let changes = container
-        .changes()
-        .await
-        .expect("container changes")
-        .unwrap_or_default();
-    assert!(changes.contains(&ContainerChangeResponseItem {
-        kind: 0,
+    let changes = container.changes().await.expect("container changes");
+    assert!(changes.contains(&FilesystemChange {
+        kind: ChangeKind::Modified,
         path: "/tmp".into()
     }));
-    assert!(changes.contains(&ContainerChangeResponseItem {
-        kind: 1,
+    assert!(changes.contains(&FilesystemChange {
+        kind: ChangeKind::Added,
         path: "/tmp/test-changes".into()
     }));
-    assert!(changes.contains(&ContainerChangeResponseItem {
-        kind: 2,
+    assert!(changes.contains(&FilesystemChange {
+        kind: ChangeKind::Deleted,
         path: "/etc/xattr.conf".into()
     }));
 
@@ -426,7 +599,8 @@ async fn container_logs() {
                 .image(DEFAULT_IMAGE)
                 .name(container_name)
                 .command(["bash", "-c", "echo 123456 && sleep inf"])
-                .build(),
+                .build()
+                .unwrap(),
         ),
     )
     .await;
@@ -451,6 +625,90 @@ async fn container_logs() {
     cleanup_container(&docker, container_name).await;
 }
 
+#[tokio::test]
+async fn container_logs_stderr() {
+    use docker_api::opts::LogsOpts;
+    let docker = init_runtime();
+
+    let container_name = "test-logs-stderr-container";
+    let container = create_base_container(
+        &docker,
+        container_name,
+        Some(
+            ContainerCreateOpts::builder()
+                .image(DEFAULT_IMAGE)
+                .name(container_name)
+                .command(["bash", "-c", "echo 654321 >&2 && sleep inf"])
+                .build()
+                .unwrap(),
+        ),
+    )
+    .await;
+
+    let _ = container.start().await;
+
+    let mut logs_stream = container.logs(&LogsOpts::builder().stdout(true).stderr(true).build());
+    match logs_stream.next().await {
+        Some(Ok(TtyChunk::StdErr(chunk))) => {
+            assert_eq!(String::from_utf8_lossy(&chunk), "654321\n");
+        }
+        chunk => {
+            eprintln!("invalid chunk {chunk:?}");
+            std::process::exit(1);
+        }
+    }
+
+    let mut logs_stream = container.logs(&LogsOpts::builder().stdout(true).build());
+    assert!(logs_stream.next().await.is_none());
+
+    cleanup_container(&docker, container_name).await;
+}
+
+#[tokio::test]
+async fn container_attach_stdin() {
+    use futures_util::AsyncWriteExt;
+    let docker = init_runtime();
+
+    let container_name = "test-attach-stdin-container";
+    let container = create_base_container(
+        &docker,
+        container_name,
+        Some(
+            ContainerCreateOpts::builder()
+                .image(DEFAULT_IMAGE)
+                .name(container_name)
+                .command(["cat"])
+                .attach_stdin(true)
+                .attach_stdout(true)
+                .build()
+                .unwrap(),
+        ),
+    )
+    .await;
+
+    let _ = container.start().await;
+
+    let multiplexer = container.attach().await.expect("attached to container");
+    let (mut reader, mut writer) = multiplexer.split();
+
+    writer
+        .write_all(b"hello attach\n")
+        .await
+        .expect("written line to stdin");
+
+    match reader.next().await {
+        Some(Ok(TtyChunk::StdOut(chunk))) => {
+            assert_eq!(String::from_utf8_lossy(&chunk), "hello attach\n");
+        }
+        chunk => {
+            eprintln!("invalid chunk {chunk:?}");
+            std::process::exit(1);
+        }
+    }
+
+    cleanup_container(&docker, container_name).await;
+}
+
 #[tokio::test]
 async fn container_stats() {
     let docker = init_runtime();
@@ -487,7 +745,11 @@ async fn container_top() {
 
     let top_result = container.top(None).await;
     assert!(top_result.is_ok());
-    assert!(top_result.unwrap().processes.unwrap_or_default()[0].contains(&DEFAULT_CMD.to_string()));
+    let top_data = top_result.unwrap();
+    assert!(top_data.processes[0].contains(&DEFAULT_CMD.to_string()));
+    assert!(top_data
+        .rows()
+        .any(|row| row.get("CMD") == Some(&DEFAULT_CMD.to_string())));
 
     cleanup_container(&docker, container_name).await;
 }
@@ -503,7 +765,8 @@ async fn containers_list() {
         .image(DEFAULT_IMAGE)
         .labels([("test-docker-list", "value")])
         .name(container_name)
-        .build();
+        .build()
+        .unwrap();
 
     let second_name = "test-list-second-container";
     let second_opts = ContainerCreateOpts::builder()
@@ -511,7 +774,8 @@ async fn containers_list() {
         .image(DEFAULT_IMAGE)
         .labels([("test-docker-list", "value2")])
         .name(second_name)
-        .build();
+        .build()
+        .unwrap();
     create_base_container(&docker, container_name, Some(opts)).await;
     create_base_container(&docker, second_name, Some(second_opts)).await;
 
@@ -585,7 +849,8 @@ async fn containers_prune() {
         .image(DEFAULT_IMAGE)
         .labels([("test-docker-prune", "value")])
         .name(container_name)
-        .build();
+        .build()
+        .unwrap();
 
     let second_name = "test-prune-second-container";
     let second_opts = ContainerCreateOpts::builder()
@@ -593,7 +858,8 @@ async fn containers_prune() {
         .image(DEFAULT_IMAGE)
         .labels([("test-docker-prune", "value2")])
         .name(second_name)
-        .build();
+        .build()
+        .unwrap();
 
     create_base_container(&docker, container_name, Some(opts.clone())).await;
     create_base_container(&docker, second_name, Some(second_opts.clone())).await;
@@ -1,6 +1,7 @@
 mod common;
 
 use common::init_runtime;
+use docker_api::ApiVersion;
 
 #[tokio::test]
 async fn docker_info() {
@@ -25,6 +26,16 @@ async fn docker_ping() {
     assert!(!ping_data.api_version.is_empty());
 }
 
+#[cfg(unix)]
+#[tokio::test]
+async fn docker_ping_unix_uri() {
+    let uri = format!("unix://{}", common::unix_socket_path().display());
+    let docker = docker_api::Docker::new(uri).expect("docker instance from unix uri");
+
+    let ping_data = docker.ping().await.expect("ping over unix socket");
+    assert!(!ping_data.api_version.is_empty());
+}
+
 #[tokio::test]
 async fn docker_version() {
     let docker = init_runtime();
@@ -38,6 +49,7 @@ async fn docker_version() {
     let ping_data = ping_result.unwrap();
 
     assert_eq!(ping_data.api_version, version_data.api_version.unwrap());
+    assert!(ping_data.api_version.parse::<ApiVersion>().is_ok());
 }
 
 #[tokio::test]
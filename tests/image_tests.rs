@@ -1,8 +1,8 @@
 mod common;
 
 use common::{
-    create_base_image, get_image_full_id, init_runtime, opts, tempdir_with_dockerfile, StreamExt,
-    TryStreamExt, DEFAULT_IMAGE,
+    create_base_image, get_image_full_id, init_runtime, opts, tempdir_with_dockerfile,
+    ImageBuildChunk, StreamExt, TryStreamExt, DEFAULT_IMAGE,
 };
 
 #[tokio::test]
@@ -42,6 +42,8 @@ async fn image_inspect() {
         .as_ref()
         .unwrap()
         .contains(&format!("{image_name}:latest")));
+    assert!(inspect_data.architecture.is_some());
+    assert!(inspect_data.os.is_some());
     assert!(image.delete().await.is_ok());
 }
 
@@ -120,16 +122,98 @@ async fn image_export_import() {
 
     assert!(image.inspect().await.is_err());
 
+    let mut loaded = Vec::new();
     let mut import_stream = images.import(&export_data[..]);
     while let Some(chunk) = import_stream.next().await {
-        assert!(chunk.is_ok());
+        if let ImageBuildChunk::Update { stream } = chunk.expect("load progress") {
+            loaded.push(stream);
+        }
     }
+    assert!(loaded
+        .iter()
+        .any(|line| line.trim() == format!("Loaded image: {}:latest", image_name)));
     assert!(image.inspect().await.is_ok());
 
+    let export_data = images
+        .export(vec![image_name, DEFAULT_IMAGE])
+        .try_concat()
+        .await
+        .expect("multi image archive");
+    assert!(!export_data.is_empty());
+
     let _ = image.delete().await;
     assert!(image.inspect().await.is_err());
 }
 
+#[tokio::test]
+async fn image_build_tar() {
+    let docker = init_runtime();
+    let images = docker.images();
+
+    let dockerfile = b"FROM alpine\nRUN true\n";
+    let mut ar = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_size(dockerfile.len() as u64);
+    header.set_mode(0o0644);
+    ar.append_data(&mut header, "Dockerfile", &dockerfile[..])
+        .unwrap();
+    let tarball = ar.into_inner().unwrap();
+
+    let tag = "test-build-tar-image";
+    let build_opts = opts::ImageBuildOpts::builder("").tag(tag).build();
+    let chunks: Vec<_> = images
+        .build_tar(
+            &build_opts,
+            futures_util::stream::iter([Ok::<_, std::io::Error>(tarball.into())]),
+        )
+        .try_collect()
+        .await
+        .expect("build output");
+
+    let id = chunks
+        .iter()
+        .find_map(|chunk| match chunk {
+            ImageBuildChunk::Digest { aux } => Some(aux.id.clone()),
+            _ => None,
+        })
+        .expect("image id of the built image");
+    assert!(id.starts_with("sha256:"));
+
+    let _ = images
+        .get(tag)
+        .remove(&opts::ImageRemoveOpts::builder().force(true).build())
+        .await;
+}
+
+#[tokio::test]
+async fn image_pull_progress() {
+    use common::models::ImageBuildChunk;
+    let docker = init_runtime();
+    let images = docker.images();
+
+    // make sure the layers are actually downloaded
+    let _ = images
+        .get("alpine:latest")
+        .remove(&opts::ImageRemoveOpts::builder().force(true).build())
+        .await;
+
+    let pull_opts = opts::PullOpts::builder()
+        .image("alpine")
+        .tag("latest")
+        .build();
+    let chunks: Vec<_> = images
+        .pull(&pull_opts)
+        .try_collect()
+        .await
+        .expect("pull progress");
+
+    assert!(chunks.iter().any(|chunk| matches!(
+        chunk,
+        ImageBuildChunk::PullStatus { status, .. } if status == "Download complete"
+    )));
+    assert!(images.get("alpine:latest").inspect().await.is_ok());
+}
+
 #[tokio::test]
 async fn image_search() {
     let docker = init_runtime();
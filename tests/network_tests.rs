@@ -35,6 +35,25 @@ async fn network_inspect() {
     assert!(network.delete().await.is_ok());
 }
 
+#[tokio::test]
+async fn network_inspect_default_bridge() {
+    let docker = init_runtime();
+
+    let inspect_data = docker
+        .networks()
+        .get("bridge")
+        .inspect()
+        .await
+        .expect("default bridge network inspect data");
+    assert_eq!(inspect_data.driver.as_deref(), Some("bridge"));
+    assert!(!inspect_data
+        .ipam
+        .expect("bridge network ipam")
+        .config
+        .unwrap_or_default()
+        .is_empty());
+}
+
 #[tokio::test]
 async fn network_prune() {
     let docker = init_runtime();
@@ -48,10 +67,12 @@ async fn network_prune() {
     let value_b = "value_b";
     let opts_a = opts::NetworkCreateOpts::builder(name_a)
         .labels([(label_key, value_a)])
-        .build();
+        .build()
+        .unwrap();
     let opts_b = opts::NetworkCreateOpts::builder(name_b)
         .labels([(label_key, value_b)])
-        .build();
+        .build()
+        .unwrap();
 
     create_base_network(&docker, name_a, Some(opts_a.clone())).await;
     create_base_network(&docker, name_b, Some(opts_b.clone())).await;
@@ -111,10 +132,12 @@ async fn network_list() {
     let value_b = "value_b";
     let opts_a = opts::NetworkCreateOpts::builder(name_a)
         .labels([(label_key, value_a)])
-        .build();
+        .build()
+        .unwrap();
     let opts_b = opts::NetworkCreateOpts::builder(name_b)
         .labels([(label_key, value_b)])
-        .build();
+        .build()
+        .unwrap();
 
     create_base_network(&docker, name_a, Some(opts_a.clone())).await;
     create_base_network(&docker, name_b, Some(opts_b.clone())).await;
@@ -0,0 +1,38 @@
+mod common;
+
+use common::{init_runtime, opts};
+
+#[tokio::test]
+async fn volume_create_inspect_delete() {
+    let docker = init_runtime();
+    let volumes = docker.volumes();
+
+    let volume_name = "test-create-volume";
+    let _ = volumes.get(volume_name).force_delete().await;
+
+    let create_opts = opts::VolumeCreateOpts::builder()
+        .name(volume_name)
+        .labels([("test-volume", "value")])
+        .build();
+    let volume_data = volumes.create(&create_opts).await.expect("created volume");
+    assert_eq!(volume_data.name, volume_name);
+
+    let volume = volumes.get(volume_name);
+    let inspect_data = volume.inspect().await.expect("volume inspect data");
+    assert_eq!(
+        inspect_data.labels.get("test-volume").map(String::as_str),
+        Some("value")
+    );
+
+    let list_opts = opts::VolumeListOpts::builder()
+        .filter([opts::VolumeFilter::Name(volume_name.to_string())])
+        .build();
+    let list_data = volumes.list(&list_opts).await.expect("volume list");
+    assert!(list_data
+        .volumes
+        .iter()
+        .any(|volume| volume.name == volume_name));
+
+    assert!(volume.force_delete().await.is_ok());
+    assert!(volume.inspect().await.is_err());
+}